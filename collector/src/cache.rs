@@ -0,0 +1,191 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use tokio::fs;
+
+use crate::http::fixture_path;
+
+/// How long cached responses are used for unless `HTTP_CACHE_TTL` says otherwise
+const DEFAULT_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Response bodies saved in raw_data, keyed by URL (minus any secrets) the same way fixtures are.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cache {
+    dir: PathBuf,
+    /// Cached responses older than this are fetched again, `None` means they never expire
+    ttl: Option<Duration>,
+    /// Ignore everything cached and fetch it all again
+    refresh: bool,
+    /// Never touch the network, serve whatever is cached no matter how old
+    offline: bool,
+}
+
+impl Cache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            ttl: Some(DEFAULT_TTL),
+            refresh: false,
+            offline: false,
+        }
+    }
+
+    /// Read from env vars, `None` if `HTTP_CACHE` is "off".
+    ///
+    /// - `HTTP_CACHE` is the dir to cache in, raw_data/http by default.
+    /// - `HTTP_CACHE_TTL` is how long responses are kept, like "90s", "30m", "12h", "7d" (the
+    ///   default), or "forever".
+    /// - `HTTP_OFFLINE` set to "true" (or "1", "yes", "on") runs entirely from the cache.
+    pub fn from_env() -> Result<Option<Self>> {
+        let dir = env::var("HTTP_CACHE").unwrap_or_else(|_| "raw_data/http".into());
+        if dir == "off" {
+            return Ok(None);
+        }
+        let ttl = match env::var("HTTP_CACHE_TTL") {
+            Ok(ttl) => parse_ttl(&ttl)?,
+            Err(_) => Some(DEFAULT_TTL),
+        };
+        let offline = match env::var("HTTP_OFFLINE") {
+            Ok(offline) => parse_offline(&offline)?,
+            Err(_) => false,
+        };
+        Ok(Some(Self::new(dir).ttl(ttl).offline(offline)))
+    }
+
+    pub fn ttl(mut self, ttl: Option<Duration>) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn refresh(mut self, refresh: bool) -> Self {
+        self.refresh = refresh;
+        self
+    }
+
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// The cached body for `url` if there is one which should be used
+    pub(crate) async fn get(&self, url: &str) -> Result<Option<String>> {
+        if self.refresh && !self.offline {
+            return Ok(None);
+        }
+        let path = fixture_path(&self.dir, url);
+        let modified = match fs::metadata(&path).await {
+            Ok(metadata) => metadata.modified()?,
+            Err(_) => return Ok(None),
+        };
+        let expired = match self.ttl {
+            // A modified time in the future counts as fresh
+            Some(ttl) => modified.elapsed().is_ok_and(|age| age > ttl),
+            None => false,
+        };
+        if expired && !self.offline {
+            return Ok(None);
+        }
+        let body = fs::read_to_string(&path)
+            .await
+            .with_context(|| format!("Could not read cached {}", path.display()))?;
+        Ok(Some(body))
+    }
+
+    pub(crate) async fn put(&self, url: &str, body: &str) -> Result<()> {
+        crate::save_fixture(&self.dir, url, body).await
+    }
+}
+
+/// A number followed by s, m, h or d, or "forever" for no TTL at all
+fn parse_ttl(ttl: &str) -> Result<Option<Duration>> {
+    if ttl == "forever" {
+        return Ok(None);
+    }
+    let invalid = || {
+        anyhow!(
+            "Invalid HTTP_CACHE_TTL {}, expected something like 90s, 30m, 12h, 7d or forever",
+            ttl
+        )
+    };
+    let unit = ttl.chars().last().ok_or_else(invalid)?;
+    let seconds = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 60 * 60,
+        'd' => 24 * 60 * 60,
+        _ => return Err(invalid()),
+    };
+    let amount: u64 = ttl[..ttl.len() - 1].parse().map_err(|_| invalid())?;
+    let seconds = amount
+        .checked_mul(seconds)
+        .ok_or_else(|| anyhow!("HTTP_CACHE_TTL {} is too long", ttl))?;
+    Ok(Some(Duration::from_secs(seconds)))
+}
+
+/// "true", "1", "yes" or "on" turn offline mode on and "false", "0", "no", "off" or nothing leave
+/// it off, in any case
+fn parse_offline(offline: &str) -> Result<bool> {
+    match offline.trim().to_lowercase().as_str() {
+        "true" | "1" | "yes" | "on" => Ok(true),
+        "false" | "0" | "no" | "off" | "" => Ok(false),
+        _ => Err(anyhow!(
+            "Invalid HTTP_OFFLINE {}, expected true or false",
+            offline
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_every_ttl_unit() {
+        assert_eq!(parse_ttl("90s").unwrap(), Some(Duration::from_secs(90)));
+        assert_eq!(
+            parse_ttl("30m").unwrap(),
+            Some(Duration::from_secs(30 * 60))
+        );
+        assert_eq!(
+            parse_ttl("12h").unwrap(),
+            Some(Duration::from_secs(12 * 60 * 60))
+        );
+        assert_eq!(parse_ttl("7d").unwrap(), Some(DEFAULT_TTL));
+        assert_eq!(parse_ttl("forever").unwrap(), None);
+    }
+
+    #[test]
+    fn rejects_invalid_ttls() {
+        for ttl in &["", "7", "d", "7w", "-7d", "1.5h"] {
+            assert!(parse_ttl(ttl).is_err(), "{}", ttl);
+        }
+        assert_eq!(
+            parse_ttl("99999999999999999d").unwrap_err().to_string(),
+            "HTTP_CACHE_TTL 99999999999999999d is too long"
+        );
+    }
+
+    #[test]
+    fn offline_is_a_boolean() {
+        for offline in &["true", "1", "yes", "ON", " True "] {
+            assert!(parse_offline(offline).unwrap(), "{}", offline);
+        }
+        for offline in &["false", "0", "no", "Off", ""] {
+            assert!(!parse_offline(offline).unwrap(), "{}", offline);
+        }
+        assert_eq!(
+            parse_offline("sometimes").unwrap_err().to_string(),
+            "Invalid HTTP_OFFLINE sometimes, expected true or false"
+        );
+    }
+}
@@ -0,0 +1,365 @@
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Context, Result};
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Client, StatusCode};
+use serde::de::DeserializeOwned;
+use tokio::fs;
+use tokio::sync::Semaphore;
+use tokio::time::delay_for;
+
+use crate::{Cache, Policy};
+
+/// Query parameters which are never part of a fixture name, so fixtures can be shared without
+/// leaking keys and replayed with any key.
+static SECRET_PARAMS: [&str; 3] = ["api_key", "key", "token"];
+
+/// Where [`Http`] gets its responses from
+#[derive(Clone, Debug, PartialEq)]
+pub enum Mode {
+    /// Always hit the network
+    Live,
+    /// Hit the network and save every response body in this fixtures dir
+    Record(PathBuf),
+    /// Only serve response bodies saved in this fixtures dir, never touch the network
+    Replay(PathBuf),
+}
+
+impl Mode {
+    /// Read from the `HTTP_MODE` env var ("live", "record" or "replay", live by default). Fixtures
+    /// are kept in the dir named by `HTTP_FIXTURES`, or "fixtures" if it isn't set.
+    pub fn from_env() -> Result<Self> {
+        let dir = PathBuf::from(env::var("HTTP_FIXTURES").unwrap_or_else(|_| "fixtures".into()));
+        match env::var("HTTP_MODE") {
+            Ok(mode) => match mode.to_lowercase().as_str() {
+                "live" => Ok(Mode::Live),
+                "record" => Ok(Mode::Record(dir)),
+                "replay" => Ok(Mode::Replay(dir)),
+                _ => Err(anyhow!(
+                    "Invalid HTTP_MODE {}, expected live, record or replay",
+                    mode
+                )),
+            },
+            Err(_) => Ok(Mode::Live),
+        }
+    }
+}
+
+/// A request which failed, even after retrying. Unlike [`reqwest::Error`] the URL never includes
+/// secrets.
+#[derive(Debug)]
+pub struct RequestError {
+    /// The URL without any secrets
+    pub url: String,
+    pub status: Option<StatusCode>,
+    pub attempts: u32,
+    message: String,
+    /// Set by a 429 or 503 which says how long to wait
+    retry_after: Option<Duration>,
+    timeout: bool,
+}
+
+impl RequestError {
+    fn new(url: &str, error: reqwest::Error) -> Self {
+        let message = match (error.status(), error.source()) {
+            (Some(status), _) => format!("HTTP {}", status),
+            (None, Some(source)) => source.to_string(),
+            (None, None) => "request failed".to_owned(),
+        };
+        Self {
+            url: strip_secrets(url),
+            status: error.status(),
+            attempts: 1,
+            message,
+            retry_after: None,
+            timeout: error.is_timeout(),
+        }
+    }
+
+    /// Too many requests, a server error, or a timeout, which might work if tried again
+    pub fn is_transient(&self) -> bool {
+        self.timeout
+            || self.status.is_some_and(|status| {
+                status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+            })
+    }
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "GET {} failed", self.url)?;
+        if self.attempts > 1 {
+            write!(f, " after {} attempts", self.attempts)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl Error for RequestError {}
+
+/// The concurrency and rate limits shared by every clone of an [`Http`]
+#[derive(Debug)]
+struct Limits {
+    policy: Policy,
+    in_flight: Semaphore,
+    /// When the next request to each rate limited host may be sent
+    next_slot: Mutex<HashMap<String, Instant>>,
+}
+
+impl Limits {
+    fn new(policy: Policy) -> Self {
+        Self {
+            in_flight: Semaphore::new(policy.max_concurrency),
+            next_slot: Mutex::new(HashMap::new()),
+            policy,
+        }
+    }
+
+    /// Claim the next slot for `host`, returning how long to wait for it
+    fn reserve(&self, host: &str) -> Duration {
+        let interval = match self.policy.interval(host) {
+            Some(interval) => interval,
+            None => return Duration::from_secs(0),
+        };
+        let mut next_slot = self.next_slot.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let slot = next_slot
+            .get(host)
+            .copied()
+            .filter(|slot| *slot > now)
+            .unwrap_or(now);
+        next_slot.insert(host.to_owned(), slot + interval);
+        slot - now
+    }
+}
+
+/// The HTTP client every collector makes requests through, so that any of them can be cached,
+/// recorded, and replayed in tests. Clones share the same concurrency and rate limits.
+#[derive(Clone, Debug)]
+pub struct Http {
+    client: Client,
+    mode: Mode,
+    cache: Option<Cache>,
+    limits: Arc<Limits>,
+}
+
+impl Default for Http {
+    fn default() -> Self {
+        Self::new(Mode::Live)
+    }
+}
+
+impl Http {
+    /// With the default [`Policy`]. Panics if the client can't be built (i.e. TLS can't be
+    /// initialized), just like [`Client::new`].
+    pub fn new(mode: Mode) -> Self {
+        Self::with_policy(mode, Policy::default()).expect("Could not build the HTTP client")
+    }
+
+    pub fn with_policy(mode: Mode, policy: Policy) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(policy.timeout)
+            .build()
+            .context("Could not build the HTTP client")?;
+        Ok(Self {
+            client,
+            mode,
+            cache: None,
+            limits: Arc::new(Limits::new(policy)),
+        })
+    }
+
+    /// See [`Mode::from_env`], [`Cache::from_env`] and [`Policy::from_env`]
+    pub fn from_env() -> Result<Self> {
+        Ok(Self::with_policy(Mode::from_env()?, Policy::from_env()?)?
+            .with_cache(Cache::from_env()?))
+    }
+
+    /// Cache every response from the network, ignored in [`Mode::Replay`]
+    pub fn with_cache(mut self, cache: Option<Cache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    pub fn mode(&self) -> &Mode {
+        &self.mode
+    }
+
+    pub fn cache(&self) -> Option<&Cache> {
+        self.cache.as_ref()
+    }
+
+    /// GET `url` with extra `headers` and return the body, from the cache if it's there. Transient
+    /// failures are retried with exponential backoff. Any other status than success is an error
+    /// (containing a [`RequestError`]) and is never cached or recorded.
+    pub async fn get_text(&self, url: &str, headers: &[(&str, &str)]) -> Result<String> {
+        match &self.mode {
+            Mode::Live => self.fetch_or_cached(url, headers).await,
+            Mode::Record(dir) => {
+                let body = self.fetch_or_cached(url, headers).await?;
+                save_fixture(dir, url, &body).await?;
+                Ok(body)
+            }
+            Mode::Replay(dir) => {
+                let path = fixture_path(dir, url);
+                fs::read_to_string(&path).await.with_context(|| {
+                    format!(
+                        "No fixture for {} at {}, record one with HTTP_MODE=record",
+                        strip_secrets(url),
+                        path.display()
+                    )
+                })
+            }
+        }
+    }
+
+    /// [`Http::get_text`] then deserialize the body as JSON
+    pub async fn get_json<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<T> {
+        let body = self.get_text(url, headers).await?;
+        serde_json::from_str(&body)
+            .with_context(|| format!("Unexpected response from {}", strip_secrets(url)))
+    }
+
+    async fn fetch_or_cached(&self, url: &str, headers: &[(&str, &str)]) -> Result<String> {
+        let cache = match &self.cache {
+            Some(cache) => cache,
+            None => return self.fetch(url, headers).await,
+        };
+        if let Some(body) = cache.get(url).await? {
+            return Ok(body);
+        }
+        if cache.is_offline() {
+            return Err(anyhow!(
+                "{} is not cached in {} and running offline",
+                strip_secrets(url),
+                cache.dir().display()
+            ));
+        }
+        let body = self.fetch(url, headers).await?;
+        cache.put(url, &body).await?;
+        Ok(body)
+    }
+
+    /// Fetch from the network, retrying transient failures
+    async fn fetch(&self, url: &str, headers: &[(&str, &str)]) -> Result<String> {
+        let policy = &self.limits.policy;
+        let mut backoff = policy.initial_backoff;
+        let mut attempts = 1;
+        loop {
+            match self.fetch_once(url, headers).await {
+                Ok(body) => return Ok(body),
+                Err(error) if error.is_transient() && attempts <= policy.retries => {
+                    delay_for(error.retry_after.unwrap_or(backoff)).await;
+                    backoff *= 2;
+                    attempts += 1;
+                }
+                Err(error) => return Err(RequestError { attempts, ..error }.into()),
+            }
+        }
+    }
+
+    async fn fetch_once(
+        &self,
+        url: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<String, RequestError> {
+        let host = reqwest::Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_owned))
+            .unwrap_or_default();
+        // Wait for the host's slot before taking a permit, so requests queued behind a rate limit
+        // don't hold every permit and starve other hosts
+        delay_for(self.limits.reserve(&host)).await;
+        let _permit = self.limits.in_flight.acquire().await;
+
+        let mut request = self.client.get(url);
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| RequestError::new(url, e))?;
+        let retry_after = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok())
+            .map(Duration::from_secs);
+        let response = response.error_for_status().map_err(|e| RequestError {
+            retry_after,
+            ..RequestError::new(url, e)
+        })?;
+        response.text().await.map_err(|e| RequestError::new(url, e))
+    }
+}
+
+/// Save `body` as the fixture for `url` in `dir`, the same way [`Mode::Record`] does. Useful for
+/// tests which build their fixtures instead of checking in hundreds of near identical files.
+pub async fn save_fixture(dir: &Path, url: &str, body: &str) -> Result<()> {
+    let path = fixture_path(dir, url);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+    fs::write(&path, body)
+        .await
+        .with_context(|| format!("Could not write fixture {}", path.display()))
+}
+
+/// The file in `dir` which holds the response for `url`. It's named after the host and path, plus
+/// a hash of the whole URL (minus [`SECRET_PARAMS`]) so that different queries don't collide.
+pub fn fixture_path(dir: &Path, url: &str) -> PathBuf {
+    let url = strip_secrets(url);
+    let without_scheme = url.split_once("://").map_or(url.as_str(), |(_, rest)| rest);
+    let without_query = without_scheme.split('?').next().unwrap_or(without_scheme);
+    let readable: String = without_query
+        .trim_end_matches('/')
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    dir.join(format!("{}-{:016x}", readable, fnv1a(url.as_bytes())))
+}
+
+/// `url` without any of the [`SECRET_PARAMS`] in its query, safe to log or save
+pub fn strip_secrets(url: &str) -> String {
+    let (base, query) = match url.split_once('?') {
+        Some(parts) => parts,
+        None => return url.to_owned(),
+    };
+    let kept: Vec<&str> = query
+        .split('&')
+        .filter(|param| {
+            let name = param.split('=').next().unwrap_or_default();
+            !SECRET_PARAMS.contains(&name)
+        })
+        .collect();
+    if kept.is_empty() {
+        base.to_owned()
+    } else {
+        format!("{}?{}", base, kept.join("&"))
+    }
+}
+
+/// 64 bit FNV-1a. Unlike `DefaultHasher` it's guaranteed to never change, which matters since
+/// fixture names are checked in.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
@@ -0,0 +1,88 @@
+#![forbid(unsafe_code)]
+
+use std::env;
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use metrics::{Metric, Source};
+
+pub use cache::Cache;
+pub use http::{fixture_path, save_fixture, strip_secrets, Http, Mode, RequestError};
+pub use pages::{fetch_pages, number, Page};
+pub use policy::Policy;
+
+pub mod testing;
+
+mod cache;
+mod http;
+mod pages;
+mod policy;
+
+/// A source of data. Fetching (all the I/O) is kept separate from parsing so that parsing can be
+/// tested without hitting the network.
+#[async_trait]
+pub trait Collector: Send + Sync {
+    /// Everything fetched from the source, before any parsing
+    type Raw: Send;
+
+    /// Unique name used to select this collector from the command line
+    fn name(&self) -> &'static str;
+
+    /// Env vars (which can be in .env) that must be set for [`Collector::fetch`] to work
+    fn required_env(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Every metric which [`Collector::parse`] produces
+    fn sources(&self) -> Vec<Source>;
+
+    async fn fetch(&self) -> Result<Self::Raw>;
+
+    fn parse(&self, raw: Self::Raw) -> Result<Vec<Metric>>;
+}
+
+/// The object safe version of [`Collector`], implemented for every `Collector`, so that a list of
+/// different collectors can be run together.
+#[async_trait]
+pub trait AnyCollector: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn sources(&self) -> Vec<Source>;
+
+    fn required_env(&self) -> &'static [&'static str];
+
+    /// Any [`Collector::required_env`] which are not set
+    fn missing_env(&self) -> Vec<&'static str>;
+
+    /// Fetch then parse
+    async fn collect(&self) -> Result<Vec<Metric>>;
+}
+
+#[async_trait]
+impl<T: Collector> AnyCollector for T {
+    fn name(&self) -> &'static str {
+        Collector::name(self)
+    }
+
+    fn sources(&self) -> Vec<Source> {
+        Collector::sources(self)
+    }
+
+    fn required_env(&self) -> &'static [&'static str] {
+        Collector::required_env(self)
+    }
+
+    fn missing_env(&self) -> Vec<&'static str> {
+        Collector::required_env(self)
+            .iter()
+            .filter(|var| env::var(var).is_err())
+            .copied()
+            .collect()
+    }
+
+    async fn collect(&self) -> Result<Vec<Metric>> {
+        let raw = self.fetch().await?;
+        self.parse(raw)
+    }
+}
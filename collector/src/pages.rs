@@ -0,0 +1,51 @@
+use std::future::Future;
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+/// One page of records from an API that pages them, along with how many there are across every
+/// page
+#[derive(Clone, Debug, PartialEq)]
+pub struct Page<T> {
+    pub total: usize,
+    pub records: Vec<T>,
+}
+
+/// Every record, calling `fetch_page` with how many there are so far (the offset of the next
+/// page) until there are as many as the last page's total. Fails if a page comes back empty
+/// before that, rather than quietly returning only some of them. `what` names the records in
+/// that error, like "parks from NPS".
+pub async fn fetch_pages<T, F, Fut>(what: &str, mut fetch_page: F) -> Result<Vec<T>>
+where
+    F: FnMut(usize) -> Fut,
+    Fut: Future<Output = Result<Page<T>>>,
+{
+    let mut records = Vec::new();
+    loop {
+        let page = fetch_page(records.len()).await?;
+        let empty = page.records.is_empty();
+        records.extend(page.records);
+        if records.len() >= page.total {
+            return Ok(records);
+        }
+        if empty {
+            return Err(anyhow!(
+                "Expected {} {} but the pages stopped after {}",
+                page.total,
+                what,
+                records.len()
+            ));
+        }
+    }
+}
+
+/// A number which an API sends as either a JSON number or a string, `None` for anything else
+/// (like null, "" or codes such as "NA") and for infinities and NaN
+pub fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(string) => string.trim().parse().ok(),
+        _ => None,
+    }
+    .filter(|number: &f64| number.is_finite())
+}
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use std::env;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+
+/// Hosts which document a rate limit, in requests per second
+static RATE_LIMITS: [(&str, f64); 1] = [
+    // 5 per second (and 10,000 per day) https://www.ncdc.noaa.gov/cdo-web/webservices/v2
+    ("www.ncdc.noaa.gov", 5.0),
+];
+
+/// How [`crate::Http`] treats the network: how many requests at once, how fast per host, and how
+/// hard to retry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Policy {
+    pub(crate) max_concurrency: usize,
+    pub(crate) timeout: Duration,
+    pub(crate) retries: u32,
+    pub(crate) initial_backoff: Duration,
+    /// Requests per second by host
+    pub(crate) rate_limits: HashMap<String, f64>,
+}
+
+impl Default for Policy {
+    /// 8 requests at once, 30 second timeouts, 5 retries starting at half a second, and the
+    /// documented [`RATE_LIMITS`].
+    fn default() -> Self {
+        Self {
+            max_concurrency: 8,
+            timeout: Duration::from_secs(30),
+            retries: 5,
+            initial_backoff: Duration::from_millis(500),
+            rate_limits: RATE_LIMITS
+                .iter()
+                .map(|(host, per_second)| ((*host).to_owned(), *per_second))
+                .collect(),
+        }
+    }
+}
+
+impl Policy {
+    /// The default, overridden by any of these env vars:
+    ///
+    /// - `HTTP_MAX_CONCURRENCY`: requests in flight at once across every collector
+    /// - `HTTP_TIMEOUT`: seconds before a request is abandoned (and retried)
+    /// - `HTTP_RETRIES`: retries after a 429, 5xx, or timeout before giving up
+    /// - `HTTP_RATE_LIMITS`: requests per second by host, like "api.eia.gov=10,www.ncdc.noaa.gov=5"
+    pub fn from_env() -> Result<Self> {
+        let mut policy = Self::default();
+        if let Ok(max) = env::var("HTTP_MAX_CONCURRENCY") {
+            policy = policy.max_concurrency(parse_env("HTTP_MAX_CONCURRENCY", &max)?);
+        }
+        if let Ok(timeout) = env::var("HTTP_TIMEOUT") {
+            policy = policy.timeout(Duration::from_secs(parse_env("HTTP_TIMEOUT", &timeout)?));
+        }
+        if let Ok(retries) = env::var("HTTP_RETRIES") {
+            policy = policy.retries(parse_env("HTTP_RETRIES", &retries)?);
+        }
+        if let Ok(limits) = env::var("HTTP_RATE_LIMITS") {
+            for limit in limits.split(',').filter(|limit| !limit.is_empty()) {
+                let (host, per_second) = limit
+                    .split_once('=')
+                    .ok_or_else(|| anyhow!("Invalid HTTP_RATE_LIMITS entry {}", limit))?;
+                policy = policy.rate_limit(host, parse_env("HTTP_RATE_LIMITS", per_second)?)?;
+            }
+        }
+        Ok(policy)
+    }
+
+    pub fn max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency.max(1);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// How long to wait before the first retry, doubled for each one after
+    pub fn initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// Space requests to `host` so there are never more than `per_second`, 0 removes its limit.
+    /// Fails if `per_second` is negative, not finite, or too small to space requests by.
+    pub fn rate_limit(mut self, host: &str, per_second: f64) -> Result<Self> {
+        spacing(per_second).map_err(|e| anyhow!("Invalid rate limit for {}: {}", host, e))?;
+        self.rate_limits.insert(host.to_owned(), per_second);
+        Ok(self)
+    }
+
+    /// The time between requests to `host`, if it is limited
+    pub(crate) fn interval(&self, host: &str) -> Option<Duration> {
+        spacing(*self.rate_limits.get(host)?).ok()?
+    }
+}
+
+/// The time between requests at `per_second`, `None` for 0 (no limit)
+fn spacing(per_second: f64) -> Result<Option<Duration>> {
+    if per_second == 0.0 {
+        return Ok(None);
+    }
+    if per_second.is_nan() || per_second < 0.0 || per_second.is_infinite() {
+        return Err(anyhow!(
+            "{} is not a number of requests per second",
+            per_second
+        ));
+    }
+    Duration::try_from_secs_f64(1.0 / per_second)
+        .map(Some)
+        .map_err(|_| anyhow!("{} requests per second is too slow", per_second))
+}
+
+fn parse_env<T: std::str::FromStr>(name: &str, value: &str) -> Result<T> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| anyhow!("Invalid {} {}", name, value))
+}
@@ -0,0 +1,37 @@
+//! Shared by every collector's tests. They all panic rather than return errors.
+
+use std::fs;
+use std::path::Path;
+
+use metrics::{Metric, Output};
+
+use crate::{AnyCollector, Http, Mode};
+
+/// A client replaying the fixtures in the crate's tests/fixtures
+pub fn fixtures() -> Http {
+    Http::new(Mode::Replay("tests/fixtures".into()))
+}
+
+/// Everything `collector` collects
+pub async fn collect(collector: impl AnyCollector) -> Vec<Metric> {
+    match collector.collect().await {
+        Ok(metrics) => metrics,
+        Err(e) => panic!("{} failed: {:#}", collector.name(), e),
+    }
+}
+
+/// The metric written to `file`
+pub fn metric<'a>(metrics: &'a [Metric], file: &str) -> &'a Metric {
+    metrics
+        .iter()
+        .find(|metric| metric.source().source == file)
+        .unwrap_or_else(|| panic!("nothing written to {}", file))
+}
+
+/// `metrics` written to an empty `generated` and sources file in `dir`
+pub fn written(dir: &Path, metrics: &[Metric]) -> Output {
+    let _ = fs::remove_dir_all(dir);
+    let output = Output::new(dir.join("generated"), dir.join("sources.json"));
+    output.write(metrics).unwrap();
+    output
+}
@@ -0,0 +1,96 @@
+use std::path::{Path, PathBuf};
+use std::thread::sleep;
+use std::time::Duration;
+
+use collector::{fixture_path, save_fixture, Cache, Http, Mode};
+
+/// Nothing listens on the discard port, so any request which reaches the network fails fast
+const URL: &str = "http://127.0.0.1:9/data?api_key=secret&id=1";
+
+/// A cache dir with a response for [`URL`] in it
+async fn cached(name: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    save_fixture(&dir, URL, "cached").await.unwrap();
+    dir
+}
+
+fn live(cache: Cache) -> Http {
+    Http::new(Mode::Live).with_cache(Some(cache))
+}
+
+#[tokio::test]
+async fn fresh_responses_come_from_the_cache() {
+    let dir = cached("fresh_responses_come_from_the_cache").await;
+    let http = live(Cache::new(&dir));
+    assert_eq!(http.get_text(URL, &[]).await.unwrap(), "cached");
+}
+
+#[tokio::test]
+async fn secrets_are_not_part_of_the_key() {
+    let dir = cached("secrets_are_not_part_of_the_key").await;
+    let http = live(Cache::new(&dir));
+    let url = "http://127.0.0.1:9/data?api_key=other&id=1";
+    assert_eq!(http.get_text(url, &[]).await.unwrap(), "cached");
+}
+
+#[tokio::test]
+async fn expired_responses_are_fetched_again() {
+    let dir = cached("expired_responses_are_fetched_again").await;
+    sleep(Duration::from_millis(20));
+    let http = live(Cache::new(&dir).ttl(Some(Duration::from_millis(1))));
+    assert!(http.get_text(URL, &[]).await.is_err());
+}
+
+#[tokio::test]
+async fn responses_without_a_ttl_never_expire() {
+    let dir = cached("responses_without_a_ttl_never_expire").await;
+    sleep(Duration::from_millis(20));
+    let http = live(Cache::new(&dir).ttl(None));
+    assert_eq!(http.get_text(URL, &[]).await.unwrap(), "cached");
+}
+
+#[tokio::test]
+async fn refresh_ignores_the_cache() {
+    let dir = cached("refresh_ignores_the_cache").await;
+    let http = live(Cache::new(&dir).refresh(true));
+    assert!(http.get_text(URL, &[]).await.is_err());
+}
+
+#[tokio::test]
+async fn offline_serves_expired_responses() {
+    let dir = cached("offline_serves_expired_responses").await;
+    sleep(Duration::from_millis(20));
+    let cache = Cache::new(&dir)
+        .ttl(Some(Duration::from_millis(1)))
+        .refresh(true)
+        .offline(true);
+    assert_eq!(live(cache).get_text(URL, &[]).await.unwrap(), "cached");
+}
+
+#[tokio::test]
+async fn offline_fails_when_nothing_is_cached() {
+    let dir = cached("offline_fails_when_nothing_is_cached").await;
+    let http = live(Cache::new(&dir).offline(true));
+    let error = http
+        .get_text("http://127.0.0.1:9/other", &[])
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("running offline"));
+}
+
+#[tokio::test]
+async fn recording_saves_cached_responses_as_fixtures() {
+    let dir = cached("recording_saves_cached_responses_as_fixtures").await;
+    let fixtures = dir.join("fixtures");
+    let http = Http::new(Mode::Record(fixtures.clone())).with_cache(Some(Cache::new(&dir)));
+    assert_eq!(http.get_text(URL, &[]).await.unwrap(), "cached");
+    assert!(fixture_path(&fixtures, URL).exists());
+}
+
+#[tokio::test]
+async fn replay_ignores_the_cache() {
+    let dir = cached("replay_ignores_the_cache").await;
+    let http = Http::new(Mode::Replay(dir.join("fixtures"))).with_cache(Some(Cache::new(&dir)));
+    assert!(http.get_text(URL, &[]).await.is_err());
+}
@@ -0,0 +1,72 @@
+use std::path::{Path, PathBuf};
+
+use collector::{fixture_path, save_fixture, strip_secrets, Http, Mode};
+
+fn fixtures_dir(name: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn secrets_are_stripped() {
+    assert_eq!(
+        strip_secrets("https://api.eia.gov/series/?api_key=abc&series_id=SEDS.TETCB.MD.A"),
+        "https://api.eia.gov/series/?series_id=SEDS.TETCB.MD.A"
+    );
+    assert_eq!(
+        strip_secrets("https://developer.nps.gov/api/v1/parks?api_key=abc"),
+        "https://developer.nps.gov/api/v1/parks"
+    );
+    assert_eq!(
+        strip_secrets("https://example.com/path"),
+        "https://example.com/path"
+    );
+}
+
+#[test]
+fn fixture_names_are_stable_and_ignore_secrets() {
+    let dir = Path::new("fixtures");
+    assert_eq!(
+        fixture_path(
+            dir,
+            "https://developer.nps.gov/api/v1/activities/parks?api_key=abc"
+        ),
+        dir.join("developer.nps.gov_api_v1_activities_parks-9beb490c0386b7f2")
+    );
+    assert_eq!(
+        fixture_path(dir, "https://api.eia.gov/series/?api_key=abc&series_id=A"),
+        fixture_path(dir, "https://api.eia.gov/series/?api_key=xyz&series_id=A"),
+    );
+    assert_ne!(
+        fixture_path(dir, "https://api.eia.gov/series/?series_id=A"),
+        fixture_path(dir, "https://api.eia.gov/series/?series_id=B"),
+    );
+}
+
+#[tokio::test]
+async fn replay_serves_saved_fixtures() {
+    let dir = fixtures_dir("replay_serves_saved_fixtures");
+    let url = "https://example.com/data?api_key=secret&id=1";
+    save_fixture(&dir, url, r#"{"value": 1}"#).await.unwrap();
+
+    let http = Http::new(Mode::Replay(dir));
+    let body = http.get_text(url, &[]).await.unwrap();
+    assert_eq!(body, r#"{"value": 1}"#);
+    let json: serde_json::Value = http
+        .get_json("https://example.com/data?id=1&api_key=other", &[])
+        .await
+        .unwrap();
+    assert_eq!(json["value"], 1);
+}
+
+#[tokio::test]
+async fn replay_never_touches_the_network() {
+    let dir = fixtures_dir("replay_never_touches_the_network");
+    let http = Http::new(Mode::Replay(dir));
+    let error = http
+        .get_text("https://example.com/missing", &[])
+        .await
+        .unwrap_err();
+    assert!(format!("{:#}", error).contains("No fixture for https://example.com/missing"));
+}
@@ -0,0 +1,39 @@
+use anyhow::Result;
+use serde_json::json;
+
+use collector::{fetch_pages, number, Page};
+
+/// Pages of up to two of the numbers below `total`, as if the API only had the first `available`
+async fn pages(total: usize, available: usize) -> Result<Vec<usize>> {
+    fetch_pages("numbers", |offset| async move {
+        Ok(Page {
+            total,
+            records: (offset..available.min(offset + 2)).collect(),
+        })
+    })
+    .await
+}
+
+#[tokio::test]
+async fn pages_are_fetched_until_the_total() {
+    assert_eq!(pages(5, 5).await.unwrap(), [0, 1, 2, 3, 4]);
+    assert_eq!(pages(0, 0).await.unwrap(), Vec::<usize>::new());
+}
+
+#[tokio::test]
+async fn pages_stopping_early_fail() {
+    assert_eq!(
+        pages(5, 3).await.unwrap_err().to_string(),
+        "Expected 5 numbers but the pages stopped after 3"
+    );
+}
+
+#[test]
+fn numbers_can_be_strings() {
+    assert_eq!(number(&json!(12.5)), Some(12.5));
+    assert_eq!(number(&json!(" 1200 ")), Some(1200.0));
+    assert_eq!(number(&json!("NA")), None);
+    assert_eq!(number(&json!("")), None);
+    assert_eq!(number(&json!(null)), None);
+    assert_eq!(number(&json!("inf")), None);
+}
@@ -0,0 +1,160 @@
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use collector::{Http, Mode, Policy, RequestError};
+
+/// A server which answers each request with the next of `responses` (status, extra headers, body)
+/// and repeats the last one forever. Returns its base URL and how many requests it got.
+fn serve(responses: Vec<(u16, &'static str, &'static str)>) -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let read = stream.read(&mut buffer).unwrap();
+                if read == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buffer[..read]);
+            }
+            let hit = counter.fetch_add(1, Ordering::SeqCst);
+            let (status, headers, body) = responses[hit.min(responses.len() - 1)];
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {} Status\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                headers,
+                body.len(),
+                body
+            );
+        }
+    });
+    (url, hits)
+}
+
+fn http(policy: Policy) -> Http {
+    Http::with_policy(Mode::Live, policy.initial_backoff(Duration::from_millis(1))).unwrap()
+}
+
+#[tokio::test]
+async fn transient_failures_are_retried() {
+    let (url, hits) = serve(vec![(503, "", ""), (429, "", ""), (200, "", "ok")]);
+    let body = http(Policy::default()).get_text(&url, &[]).await.unwrap();
+    assert_eq!(body, "ok");
+    assert_eq!(hits.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn retries_give_up_eventually() {
+    let (url, hits) = serve(vec![(500, "", "")]);
+    let error = http(Policy::default().retries(2))
+        .get_text(&format!("{}/series?api_key=secret&id=1", url), &[])
+        .await
+        .unwrap_err();
+    assert_eq!(hits.load(Ordering::SeqCst), 3);
+    let error = error.downcast::<RequestError>().unwrap();
+    assert_eq!(error.attempts, 3);
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "GET {}/series?id=1 failed after 3 attempts: HTTP 500 Internal Server Error",
+            url
+        )
+    );
+}
+
+#[tokio::test]
+async fn client_errors_are_not_retried() {
+    let (url, hits) = serve(vec![(404, "", ""), (200, "", "ok")]);
+    let error = http(Policy::default())
+        .get_text(&url, &[])
+        .await
+        .unwrap_err();
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+    assert!(!error.downcast::<RequestError>().unwrap().is_transient());
+}
+
+#[tokio::test]
+async fn retry_after_is_respected() {
+    let (url, _) = serve(vec![(429, "Retry-After: 1\r\n", ""), (200, "", "ok")]);
+    let start = Instant::now();
+    let body = http(Policy::default()).get_text(&url, &[]).await.unwrap();
+    assert_eq!(body, "ok");
+    assert!(start.elapsed() >= Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn rate_limits_space_out_requests_to_a_host() {
+    let (url, hits) = serve(vec![(200, "", "ok")]);
+    let http = http(Policy::default().rate_limit("127.0.0.1", 20.0).unwrap());
+    let start = Instant::now();
+    for _ in 0..5 {
+        http.get_text(&url, &[]).await.unwrap();
+    }
+    assert_eq!(hits.load(Ordering::SeqCst), 5);
+    // The first request goes right away, then one every 50ms
+    assert!(start.elapsed() >= Duration::from_millis(200));
+}
+
+#[tokio::test]
+async fn rate_limited_requests_dont_starve_other_hosts() {
+    let (url, _) = serve(vec![(200, "", "ok")]);
+    let other_host = url.replace("127.0.0.1", "localhost");
+    let http = http(
+        Policy::default()
+            .max_concurrency(1)
+            .rate_limit("127.0.0.1", 1.0)
+            .unwrap(),
+    );
+    let limited = async {
+        tokio::join!(
+            http.get_text(&url, &[]),
+            http.get_text(&url, &[]),
+            http.get_text(&url, &[])
+        )
+    };
+    let other = async {
+        let start = Instant::now();
+        http.get_text(&other_host, &[]).await.unwrap();
+        start.elapsed()
+    };
+    let (_, elapsed) = tokio::join!(limited, other);
+    // The second and third requests to 127.0.0.1 wait a second each for their slot, without
+    // holding the only permit
+    assert!(elapsed < Duration::from_millis(500));
+}
+
+#[test]
+fn rate_limits_must_be_usable() {
+    let policy = Policy::default();
+    assert!(policy.clone().rate_limit("127.0.0.1", 0.0).is_ok());
+    assert!(policy.clone().rate_limit("127.0.0.1", 0.001).is_ok());
+    for per_second in &[
+        -1.0,
+        f64::NAN,
+        f64::INFINITY,
+        1e-300,
+        f64::MIN_POSITIVE / 2.0,
+    ] {
+        let error = policy
+            .clone()
+            .rate_limit("127.0.0.1", *per_second)
+            .unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with("Invalid rate limit for 127.0.0.1: "),
+            "{}",
+            error
+        );
+    }
+}
@@ -0,0 +1,188 @@
+#![forbid(unsafe_code)]
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use select::document::Document;
+use select::node::Node;
+use select::predicate::{Name, Predicate};
+
+use collector::{Collector, Http};
+use metrics::{DataType, Metric, Sort, Source};
+use states::{Regions, Resolver};
+
+const URL: &str = "https://meric.mo.gov/data/cost-living-data-series";
+
+static COST_RANK: Source = Source {
+    source: Cow::Borrowed("cost_rank.json"),
+    name: Cow::Borrowed("Cost Rank"),
+    data_type: DataType::Integer,
+    source_url: Cow::Borrowed(URL),
+    description: Cow::Borrowed("The rank of the state by overall cost of living. 1 is the cheapest state to live in, 51 is the most expensive. MERIC ranks DC along with the states, so a rank is skipped when DC isn't included."),
+    min: Some(1.0),
+    max: Some(51.0),
+    sort: Sort::Ascending,
+    ..Source::BASE
+};
+
+static COST_INDEX: Source = Source {
+    source: Cow::Borrowed("cost_index.json"),
+    name: Cow::Borrowed("Cost Index"),
+    data_type: DataType::Number,
+    source_url: Cow::Borrowed(URL),
+    description: Cow::Borrowed("Cost Index, where the overall for the country is 100. A higher number indicates a higher cost."),
+    units: Some(Cow::Borrowed("index")),
+    sort: Sort::Ascending,
+    ..Source::BASE
+};
+
+static GROCERY_COST_INDEX: Source = Source {
+    source: Cow::Borrowed("grocery_cost_index.json"),
+    name: Cow::Borrowed("Grocery Cost Index"),
+    data_type: DataType::Number,
+    source_url: Cow::Borrowed(URL),
+    description: Cow::Borrowed("Cost Index for groceries, where the overall for the country is 100. A higher number indicates a higher cost."),
+    units: Some(Cow::Borrowed("index")),
+    sort: Sort::Ascending,
+    ..Source::BASE
+};
+
+static HEALTH_COST_INDEX: Source = Source {
+    source: Cow::Borrowed("health_cost_index.json"),
+    name: Cow::Borrowed("Health Cost Index"),
+    data_type: DataType::Number,
+    source_url: Cow::Borrowed(URL),
+    description: Cow::Borrowed("Cost Index for health, where the overall for the country is 100. A higher number indicates a higher cost."),
+    units: Some(Cow::Borrowed("index")),
+    sort: Sort::Ascending,
+    ..Source::BASE
+};
+
+static HOUSING_COST_INDEX: Source = Source {
+    source: Cow::Borrowed("housing_cost_index.json"),
+    name: Cow::Borrowed("Housing Cost Index"),
+    data_type: DataType::Number,
+    source_url: Cow::Borrowed(URL),
+    description: Cow::Borrowed("Cost Index for housing, where the overall for the country is 100. A higher number indicates a higher cost."),
+    units: Some(Cow::Borrowed("index")),
+    sort: Sort::Ascending,
+    ..Source::BASE
+};
+
+static TRANSPORTATION_COST_INDEX: Source = Source {
+    source: Cow::Borrowed("transportation_cost_index.json"),
+    name: Cow::Borrowed("Transportation Cost Index"),
+    data_type: DataType::Number,
+    source_url: Cow::Borrowed(URL),
+    description: Cow::Borrowed("Cost Index for transportation, where the overall for the country is 100. A higher number indicates a higher cost."),
+    units: Some(Cow::Borrowed("index")),
+    sort: Sort::Ascending,
+    ..Source::BASE
+};
+
+static UTILITIES_COST_INDEX: Source = Source {
+    source: Cow::Borrowed("utilities_cost_index.json"),
+    name: Cow::Borrowed("Utilities Cost Index"),
+    data_type: DataType::Number,
+    source_url: Cow::Borrowed(URL),
+    description: Cow::Borrowed("Cost Index for utilities, where the overall for the country is 100. A higher number indicates a higher cost."),
+    units: Some(Cow::Borrowed("index")),
+    sort: Sort::Ascending,
+    ..Source::BASE
+};
+
+/// Scrapes the cost of living table, one metric per column
+pub struct CostOfLiving {
+    regions: Regions,
+    http: Http,
+}
+
+impl CostOfLiving {
+    pub fn new(regions: Regions, http: Http) -> Self {
+        Self { regions, http }
+    }
+}
+
+#[async_trait]
+impl Collector for CostOfLiving {
+    /// The HTML of the page containing the table
+    type Raw = String;
+
+    fn name(&self) -> &'static str {
+        "cost_of_living"
+    }
+
+    fn sources(&self) -> Vec<Source> {
+        vec![
+            COST_RANK.clone(),
+            COST_INDEX.clone(),
+            GROCERY_COST_INDEX.clone(),
+            HOUSING_COST_INDEX.clone(),
+            UTILITIES_COST_INDEX.clone(),
+            TRANSPORTATION_COST_INDEX.clone(),
+            HEALTH_COST_INDEX.clone(),
+        ]
+    }
+
+    async fn fetch(&self) -> Result<Self::Raw> {
+        self.http.get_text(URL, &[]).await
+    }
+
+    fn parse(&self, html: Self::Raw) -> Result<Vec<Metric>> {
+        let document = Document::from(&*html);
+        let table = document
+            .find(Name("table"))
+            .next()
+            .ok_or_else(|| anyhow!("No table found at {}", URL))?;
+
+        let mut cost_ranks = HashMap::with_capacity(50);
+        let mut overall = HashMap::with_capacity(50);
+        let mut grocery = HashMap::with_capacity(50);
+        let mut housing = HashMap::with_capacity(50);
+        let mut utilities = HashMap::with_capacity(50);
+        let mut transportation = HashMap::with_capacity(50);
+        let mut health = HashMap::with_capacity(50);
+
+        let mut resolver = Resolver::new();
+        for node in table.find(Name("tbody").descendant(Name("tr"))) {
+            let mut columns = node.find(Name("td"));
+            let state = next_column(&mut columns, "state")?.text();
+            let state_code = match resolver.resolve(&state) {
+                Some(state_code) if self.regions.contains(state_code) => state_code,
+                _ => continue,
+            };
+            cost_ranks.insert(state_code, parse_column(&mut columns, "rank")?);
+            overall.insert(state_code, parse_column(&mut columns, "index")?);
+            grocery.insert(state_code, parse_column(&mut columns, "grocery")?);
+            housing.insert(state_code, parse_column(&mut columns, "housing")?);
+            utilities.insert(state_code, parse_column(&mut columns, "utilities")?);
+            transportation.insert(state_code, parse_column(&mut columns, "transportation")?);
+            health.insert(state_code, parse_column(&mut columns, "health")?);
+        }
+        resolver.warn_unresolved(URL);
+        Ok(vec![
+            Metric::new(COST_RANK.clone(), cost_ranks),
+            Metric::new(COST_INDEX.clone(), overall),
+            Metric::new(GROCERY_COST_INDEX.clone(), grocery),
+            Metric::new(HOUSING_COST_INDEX.clone(), housing),
+            Metric::new(UTILITIES_COST_INDEX.clone(), utilities),
+            Metric::new(TRANSPORTATION_COST_INDEX.clone(), transportation),
+            Metric::new(HEALTH_COST_INDEX.clone(), health),
+        ])
+    }
+}
+
+fn next_column<'a>(columns: &mut impl Iterator<Item = Node<'a>>, name: &str) -> Result<Node<'a>> {
+    columns
+        .next()
+        .ok_or_else(|| anyhow!("Row is missing the {} column", name))
+}
+
+fn parse_column<'a>(columns: &mut impl Iterator<Item = Node<'a>>, name: &str) -> Result<f64> {
+    let text = next_column(columns, name)?.text();
+    text.trim()
+        .parse()
+        .map_err(|_| anyhow!("Could not parse {} {:?}", name, text))
+}
@@ -0,0 +1,74 @@
+use std::path::Path;
+
+use collector::testing::{collect, fixtures, metric, written};
+use cost_of_living::CostOfLiving;
+use metrics::validate_file;
+use states::{Regions, State};
+
+#[tokio::test]
+async fn parses_every_column() {
+    let metrics = collect(CostOfLiving::new(Regions::States, fixtures())).await;
+    assert_eq!(metrics.len(), 7);
+    assert_eq!(
+        metric(&metrics, "cost_rank.json").get(State::Mississippi),
+        Some(1.0)
+    );
+    assert_eq!(
+        metric(&metrics, "cost_index.json").get(State::Oklahoma),
+        Some(86.0)
+    );
+    assert_eq!(
+        metric(&metrics, "grocery_cost_index.json").get(State::Maryland),
+        Some(107.7)
+    );
+    assert_eq!(
+        metric(&metrics, "housing_cost_index.json").get(State::Hawaii),
+        Some(318.6)
+    );
+    assert_eq!(
+        metric(&metrics, "utilities_cost_index.json").get(State::Maryland),
+        Some(117.0)
+    );
+    assert_eq!(
+        metric(&metrics, "transportation_cost_index.json").get(State::Mississippi),
+        Some(89.1)
+    );
+    assert_eq!(
+        metric(&metrics, "health_cost_index.json").get(State::Oklahoma),
+        Some(93.0)
+    );
+}
+
+#[tokio::test]
+async fn dc_follows_regions() {
+    let states = collect(CostOfLiving::new(Regions::States, fixtures())).await;
+    assert_eq!(
+        metric(&states, "cost_index.json").get(State::DistrictOfColumbia),
+        None
+    );
+
+    let with_dc = collect(CostOfLiving::new(Regions::StatesAndDc, fixtures())).await;
+    assert_eq!(
+        metric(&with_dc, "cost_index.json").get(State::DistrictOfColumbia),
+        Some(152.1)
+    );
+}
+
+#[tokio::test]
+async fn fixture_output_is_valid() {
+    // MERIC ranks DC too, so its ranks must be valid with and without it
+    for &regions in &[Regions::States, Regions::StatesAndDc] {
+        let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("{:?}", regions));
+        let metrics = collect(CostOfLiving::new(regions, fixtures())).await;
+        let output = written(&dir, &metrics);
+        for metric in &metrics {
+            // The fixture only has a few of the states, the rest are reported missing
+            let problems: Vec<String> = validate_file(&output, metric.source(), regions)
+                .iter()
+                .map(|problem| problem.message.clone())
+                .filter(|message| !message.contains("missing "))
+                .collect();
+            assert_eq!(problems, Vec::<String>::new(), "{}", metric.source().source);
+        }
+    }
+}
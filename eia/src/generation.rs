@@ -0,0 +1,70 @@
+//! The fuels whose share of generation is collected, https://www.eia.gov/electricity/data/browser/.
+
+use std::borrow::Cow;
+
+use metrics::{DataType, Sort, Source};
+
+/// The v2 route with net generation by state, sector and fuel
+pub(crate) const ROUTE: &str = "electricity/electric-power-operational-data";
+
+/// The data column, in thousand MWh
+pub(crate) const GENERATION: &str = "generation";
+
+/// The `sectorid` of every sector combined (utilities, independent power producers, commercial
+/// and industrial)
+pub(crate) const ALL_SECTORS: &str = "99";
+
+/// The `fueltypeid` of generation from every fuel, which each share is divided by
+pub(crate) const ALL_FUELS: &str = "ALL";
+
+/// A metric for the share of generation from one fuel
+pub(crate) struct GenerationShare {
+    pub(crate) source: Source,
+    /// The fuel's `fueltypeid`
+    pub(crate) fuel: &'static str,
+}
+
+macro_rules! generation_share {
+    ($file:literal, $name:literal, $fuel_id:literal, $fuel:literal) => {
+        GenerationShare {
+            source: Source {
+                source: Cow::Borrowed(concat!($file, ".json")),
+                name: Cow::Borrowed($name),
+                data_type: DataType::Percent,
+                source_url: Cow::Borrowed("https://www.eia.gov/electricity/data/browser/"),
+                description: Cow::Borrowed(concat!(
+                    "Percent of the electricity generated in the state that came from ",
+                    $fuel,
+                    ", by MWh of net generation at utility-scale plants, from the latest year \
+                     available."
+                )),
+                min: Some(0.0),
+                max: Some(1.0),
+                sort: Sort::Descending,
+                history: Some(Cow::Borrowed(concat!($file, "_history.json"))),
+                ..Source::BASE
+            },
+            fuel: $fuel_id,
+        }
+    };
+}
+
+pub(crate) static SHARES: [GenerationShare; 5] = [
+    generation_share!("percent_coal", "Coal Power", "COW", "coal"),
+    generation_share!(
+        "percent_natural_gas",
+        "Natural Gas Power",
+        "NG",
+        "natural gas"
+    ),
+    generation_share!("percent_nuclear", "Nuclear Power", "NUC", "nuclear"),
+    generation_share!("percent_wind", "Wind Power", "WND", "wind"),
+    generation_share!("percent_solar", "Solar Power", "SUN", "solar"),
+];
+
+/// Every `fueltypeid` to fetch: each share's fuel and [`ALL_FUELS`]
+pub(crate) fn fuel_ids() -> Vec<&'static str> {
+    std::iter::once(ALL_FUELS)
+        .chain(SHARES.iter().map(|share| share.fuel))
+        .collect()
+}
@@ -0,0 +1,281 @@
+#![forbid(unsafe_code)]
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::env;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::future::join3;
+
+use collector::{Collector, Http};
+use generation::{ALL_FUELS, ALL_SECTORS, GENERATION, SHARES};
+use metrics::{DataType, History, Metric, Sort, Source};
+use seds::{SedsMetric, METRICS};
+use states::{Regions, State};
+use v2::{Query, Row};
+
+mod generation;
+mod seds;
+pub mod v2;
+
+static CO2_EMISSIONS: Source = Source {
+    source: Cow::Borrowed("co2_emissions.json"),
+    name: Cow::Borrowed("CO2 Emissions"),
+    data_type: DataType::Number,
+    source_url: Cow::Borrowed("https://www.eia.gov/environment/emissions/state/analysis/"),
+    description: Cow::Borrowed(
+        "Total carbon dioxide emissions from all sectors in million metric tons, from the latest year available.",
+    ),
+    units: Some(Cow::Borrowed("mmt CO2")),
+    sort: Sort::Ascending,
+    history: Some(Cow::Borrowed("co2_emissions_history.json")),
+    ..Source::BASE
+};
+
+/// Every response from the EIA API, or why it failed. One query failing only loses its own
+/// metrics.
+pub struct Raw {
+    emissions: Result<Vec<Row>>,
+    /// Every series in [`seds::METRICS`]
+    seds: Result<Vec<Row>>,
+    /// Generation from every fuel in [`generation::SHARES`], and in total
+    generation: Result<Vec<Row>>,
+}
+
+/// Fetches CO2 emissions, the SEDS metrics and generation shares
+pub struct Eia {
+    regions: Regions,
+    http: Http,
+}
+
+impl Eia {
+    pub fn new(regions: Regions, http: Http) -> Self {
+        Self { regions, http }
+    }
+
+    /// Every year of `rows` with a value in `column`, by state. Rows are for every state at once
+    /// (plus the U.S. total), so this is where they're narrowed down to `regions`.
+    fn history<'a>(&self, rows: impl Iterator<Item = &'a Row>, column: &str) -> Result<History> {
+        let mut history = History::new();
+        for row in rows {
+            let state = match row.state() {
+                Some(state) if self.regions.contains(state) && is_covered(state) => state,
+                _ => continue,
+            };
+            if let Some(value) = row.value(column) {
+                history.entry(state).or_default().insert(row.year()?, value);
+            }
+        }
+        Ok(history)
+    }
+
+    /// Why each state EIA covers in `regions` has no value in `metric`
+    fn missing(&self, metric: &Metric) -> Vec<(State, String)> {
+        let history = metric.history();
+        self.regions
+            .iter()
+            .filter(|state| is_covered(*state) && metric.get(*state).is_none())
+            .map(|state| {
+                match (
+                    history.and_then(|history| history.get(&state)),
+                    metric.year(),
+                ) {
+                    (Some(_), Some(year)) => (state, format!("EIA has no data for {}", year)),
+                    _ => (state, "EIA has no data".to_owned()),
+                }
+            })
+            .collect()
+    }
+
+    /// A metric with every year of `history`, and every covered state without a value missing
+    fn metric(&self, source: &Source, history: History) -> Metric {
+        let metric = Metric::with_history(source.clone(), history);
+        let missing = self.missing(&metric);
+        metric.with_missing(missing)
+    }
+
+    /// Every metric in [`METRICS`], computed from the rows of every series
+    fn seds(&self, seds: &[Row]) -> Result<Vec<Metric>> {
+        let series = series_ids()
+            .into_iter()
+            .map(|id| {
+                let rows = seds.iter().filter(|row| row.facet("seriesId") == Some(id));
+                Ok((id, self.history(rows, "value")?))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+        let mut metrics = Vec::with_capacity(METRICS.len());
+        for metric in &METRICS {
+            let numerator = &series[metric.numerator];
+            let history = match metric.denominator {
+                Some(denominator) => divide(numerator, &series[denominator]),
+                None => numerator.clone(),
+            };
+            let history = history
+                .into_iter()
+                .map(|(state, years)| {
+                    let years = years
+                        .into_iter()
+                        .map(|(year, value)| (year, value * metric.scale))
+                        .collect();
+                    (state, years)
+                })
+                .collect();
+            metrics.push(self.metric(&metric.source, history));
+        }
+        Ok(metrics)
+    }
+
+    /// Each fuel's generation divided by generation from every fuel. A fuel without a row for a
+    /// year the state has a total for generated none of it, while a row without a value (i.e.
+    /// withheld) leaves that year out.
+    fn generation_shares(&self, rows: &[Row]) -> Result<Vec<Metric>> {
+        let fuel = |id: &'static str| {
+            rows.iter()
+                .filter(move |row| row.facet("fueltypeid") == Some(id))
+        };
+        let total = self.history(fuel(ALL_FUELS), GENERATION)?;
+        let mut metrics = Vec::with_capacity(SHARES.len());
+        for share in &SHARES {
+            let generated = self.history(fuel(share.fuel), GENERATION)?;
+            let mut reported = HashSet::new();
+            for row in fuel(share.fuel) {
+                reported.insert((row.state(), row.year()?));
+            }
+            let generated: History = total
+                .iter()
+                .map(|(state, years)| {
+                    let years = years
+                        .keys()
+                        .filter_map(|year| {
+                            match generated.get(state).and_then(|years| years.get(year)) {
+                                Some(value) => Some((*year, *value)),
+                                None if reported.contains(&(Some(*state), *year)) => None,
+                                None => Some((*year, 0.0)),
+                            }
+                        })
+                        .collect();
+                    (*state, years)
+                })
+                .collect();
+            metrics.push(self.metric(&share.source, divide(&generated, &total)));
+        }
+        Ok(metrics)
+    }
+}
+
+/// The rows of a query, or `None` if it failed, which is logged. There's a single query for every
+/// state, so writing `null` everywhere would only throw away whatever was written last time.
+fn rows(rows: Result<Vec<Row>>) -> Option<Vec<Row>> {
+    match rows {
+        Ok(rows) => Some(rows),
+        Err(error) => {
+            eprintln!("Warning: {:#}, skipping its metrics", error);
+            None
+        }
+    }
+}
+
+#[async_trait]
+impl Collector for Eia {
+    type Raw = Raw;
+
+    fn name(&self) -> &'static str {
+        "eia"
+    }
+
+    fn required_env(&self) -> &'static [&'static str] {
+        &["EIA_KEY"]
+    }
+
+    fn sources(&self) -> Vec<Source> {
+        std::iter::once(CO2_EMISSIONS.clone())
+            .chain(METRICS.iter().map(|metric| metric.source.clone()))
+            .chain(SHARES.iter().map(|share| share.source.clone()))
+            .collect()
+    }
+
+    async fn fetch(&self) -> Result<Self::Raw> {
+        let api_key = env::var("EIA_KEY")?;
+        let emissions = Query::new("co2-emissions/co2-emissions-aggregates")
+            .frequency("annual")
+            .data("value")
+            .facet("sectorId", &["TT"])
+            .facet("fuelId", &["TO"]);
+        let seds = Query::new("seds")
+            .frequency("annual")
+            .data("value")
+            .facet("seriesId", &series_ids());
+        let generation = Query::new(generation::ROUTE)
+            .frequency("annual")
+            .data(GENERATION)
+            .facet("sectorid", &[ALL_SECTORS])
+            .facet("fueltypeid", &generation::fuel_ids());
+        let (emissions, seds, generation) = join3(
+            emissions.fetch(&api_key, &self.http),
+            seds.fetch(&api_key, &self.http),
+            generation.fetch(&api_key, &self.http),
+        )
+        .await;
+        match (emissions, seds, generation) {
+            (Err(error), Err(_), Err(_)) => Err(error),
+            (emissions, seds, generation) => Ok(Raw {
+                emissions,
+                seds,
+                generation,
+            }),
+        }
+    }
+
+    fn parse(&self, raw: Self::Raw) -> Result<Vec<Metric>> {
+        let mut metrics = Vec::with_capacity(METRICS.len() + SHARES.len() + 1);
+        if let Some(emissions) = rows(raw.emissions) {
+            let history = self.history(emissions.iter(), "value")?;
+            metrics.push(self.metric(&CO2_EMISSIONS, history));
+        }
+        if let Some(seds) = rows(raw.seds) {
+            metrics.extend(self.seds(&seds)?);
+        }
+        if let Some(generation) = rows(raw.generation) {
+            metrics.extend(self.generation_shares(&generation)?);
+        }
+        Ok(metrics)
+    }
+}
+
+/// Every SEDS series any metric needs, once each
+fn series_ids() -> Vec<&'static str> {
+    let mut ids: Vec<&'static str> = Vec::new();
+    for id in METRICS.iter().flat_map(SedsMetric::series) {
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    ids
+}
+
+/// `numerator` / `denominator` for every state and year both have, skipping a denominator of 0
+fn divide(numerator: &History, denominator: &History) -> History {
+    numerator
+        .iter()
+        .map(|(state, years)| {
+            let shares = years
+                .iter()
+                .filter_map(|(year, numerator)| {
+                    match denominator.get(state).and_then(|years| years.get(year)) {
+                        Some(denominator) if *denominator != 0.0 => {
+                            Some((*year, numerator / denominator))
+                        }
+                        _ => None,
+                    }
+                })
+                .collect();
+            (*state, shares)
+        })
+        .collect()
+}
+
+/// EIA publishes data for every state and DC, but not the territories.
+fn is_covered(state: State) -> bool {
+    state.is_state() || state == State::DistrictOfColumbia
+}
@@ -0,0 +1,121 @@
+//! The metrics computed from State Energy Data System series, https://www.eia.gov/state/seds/.
+
+use std::borrow::Cow;
+
+use metrics::{DataType, Sort, Source};
+
+/// Btu in a kWh, EIA's conversion for electricity
+const BTU_PER_KWH: f64 = 3412.0;
+
+/// A metric computed from SEDS series: `numerator`, divided by `denominator` if there is one, then
+/// multiplied by `scale`.
+pub(crate) struct SedsMetric {
+    pub(crate) source: Source,
+    pub(crate) numerator: &'static str,
+    pub(crate) denominator: Option<&'static str>,
+    /// Converts the series' units to the source's units
+    pub(crate) scale: f64,
+}
+
+impl SedsMetric {
+    /// Every series needed to compute this metric
+    pub(crate) fn series(&self) -> impl Iterator<Item = &'static str> {
+        std::iter::once(self.numerator).chain(self.denominator)
+    }
+}
+
+pub(crate) static METRICS: [SedsMetric; 5] = [
+    SedsMetric {
+        source: Source {
+            source: Cow::Borrowed("percent_renewable.json"),
+            name: Cow::Borrowed("Renewable Energy"),
+            data_type: DataType::Percent,
+            source_url: Cow::Borrowed("https://www.eia.gov/opendata/browser/seds"),
+            description: Cow::Borrowed(
+                "Percent of energy consumption that was renewable, from the latest year available.",
+            ),
+            sort: Sort::Descending,
+            history: Some(Cow::Borrowed("percent_renewable_history.json")),
+            ..Source::BASE
+        },
+        numerator: "RETCB",
+        denominator: Some("TETCB"),
+        scale: 1.0,
+    },
+    SedsMetric {
+        source: Source {
+            source: Cow::Borrowed("energy_per_capita.json"),
+            name: Cow::Borrowed("Energy Use per Person"),
+            data_type: DataType::Number,
+            source_url: Cow::Borrowed("https://www.eia.gov/state/seds/"),
+            description: Cow::Borrowed(
+                "Total energy consumption per person in million Btu, from the latest year available.",
+            ),
+            units: Some(Cow::Borrowed("million Btu")),
+            min: Some(0.0),
+            sort: Sort::Ascending,
+            history: Some(Cow::Borrowed("energy_per_capita_history.json")),
+            ..Source::BASE
+        },
+        numerator: "TETPB",
+        denominator: None,
+        scale: 1.0,
+    },
+    SedsMetric {
+        source: Source {
+            source: Cow::Borrowed("residential_electricity_price.json"),
+            name: Cow::Borrowed("Residential Electricity Price"),
+            data_type: DataType::Number,
+            source_url: Cow::Borrowed("https://www.eia.gov/state/seds/"),
+            description: Cow::Borrowed(
+                "Average price of electricity for homes in cents per kWh, from the latest year available.",
+            ),
+            units: Some(Cow::Borrowed("¢/kWh")),
+            min: Some(0.0),
+            sort: Sort::Ascending,
+            history: Some(Cow::Borrowed("residential_electricity_price_history.json")),
+            ..Source::BASE
+        },
+        // Dollars per million Btu
+        numerator: "ESRCD",
+        denominator: None,
+        scale: 100.0 * BTU_PER_KWH / 1_000_000.0,
+    },
+    SedsMetric {
+        source: Source {
+            source: Cow::Borrowed("energy_expenditure_per_capita.json"),
+            name: Cow::Borrowed("Energy Spending per Person"),
+            data_type: DataType::Money,
+            source_url: Cow::Borrowed("https://www.eia.gov/state/seds/"),
+            description: Cow::Borrowed(
+                "Total spending on energy per person in dollars, from the latest year available.",
+            ),
+            sort: Sort::Ascending,
+            history: Some(Cow::Borrowed("energy_expenditure_per_capita_history.json")),
+            ..Source::BASE
+        },
+        numerator: "TETPV",
+        denominator: None,
+        scale: 1.0,
+    },
+    SedsMetric {
+        source: Source {
+            source: Cow::Borrowed("population.json"),
+            name: Cow::Borrowed("Population"),
+            data_type: DataType::Integer,
+            source_url: Cow::Borrowed("https://www.eia.gov/state/seds/"),
+            description: Cow::Borrowed(
+                "Resident population, from the latest year available. Used to compute per capita metrics.",
+            ),
+            units: Some(Cow::Borrowed("people")),
+            min: Some(0.0),
+            sort: Sort::Descending,
+            history: Some(Cow::Borrowed("population_history.json")),
+            ..Source::BASE
+        },
+        // Thousands of people
+        numerator: "TPOPP",
+        denominator: None,
+        scale: 1000.0,
+    },
+];
@@ -0,0 +1,195 @@
+//! A small client for version 2 of the EIA API, https://www.eia.gov/opendata/documentation.php.
+//! Every dataset is a route, which is filtered by facets (like `stateId`) and returns rows with a
+//! period, the facets, and whichever data columns were asked for.
+
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+
+use collector::{fetch_pages, number, Http};
+use states::State;
+
+/// The most rows the API will return in one response
+pub const MAX_LENGTH: usize = 5000;
+
+/// A request for the data of one route, built up like
+/// `Query::new("seds").frequency("annual").data("value").facet("seriesId", &["TETCB"])`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Query {
+    route: String,
+    frequency: Option<String>,
+    data: Vec<String>,
+    facets: BTreeMap<String, Vec<String>>,
+    start: Option<String>,
+    end: Option<String>,
+    /// Rows per page
+    length: usize,
+}
+
+impl Query {
+    /// `route` is everything between `/v2/` and `/data/`, like
+    /// "co2-emissions/co2-emissions-aggregates"
+    pub fn new(route: &str) -> Self {
+        Self {
+            route: route.trim_matches('/').to_owned(),
+            frequency: None,
+            data: Vec::new(),
+            facets: BTreeMap::new(),
+            start: None,
+            end: None,
+            length: MAX_LENGTH,
+        }
+    }
+
+    /// Like "annual" or "monthly"
+    pub fn frequency(mut self, frequency: &str) -> Self {
+        self.frequency = Some(frequency.to_owned());
+        self
+    }
+
+    /// Include a data column (usually "value") in every row
+    pub fn data(mut self, column: &str) -> Self {
+        self.data.push(column.to_owned());
+        self
+    }
+
+    /// Only return rows where the facet `name` is one of `values`
+    pub fn facet<S: AsRef<str>>(mut self, name: &str, values: &[S]) -> Self {
+        self.facets
+            .entry(name.to_owned())
+            .or_default()
+            .extend(values.iter().map(|value| value.as_ref().to_owned()));
+        self
+    }
+
+    /// The first period to include, formatted like the frequency's periods (e.g. "2010")
+    pub fn start(mut self, start: &str) -> Self {
+        self.start = Some(start.to_owned());
+        self
+    }
+
+    /// The last period to include
+    pub fn end(mut self, end: &str) -> Self {
+        self.end = Some(end.to_owned());
+        self
+    }
+
+    /// How many rows to ask for at once, capped at [`MAX_LENGTH`]
+    pub fn length(mut self, length: usize) -> Self {
+        self.length = length.clamp(1, MAX_LENGTH);
+        self
+    }
+
+    /// The URL of the page of rows starting at `offset`
+    pub fn url(&self, api_key: &str, offset: usize) -> String {
+        let mut url = format!(
+            "https://api.eia.gov/v2/{}/data/?api_key={}",
+            self.route, api_key
+        );
+        if let Some(frequency) = &self.frequency {
+            url.push_str(&format!("&frequency={}", frequency));
+        }
+        for (index, column) in self.data.iter().enumerate() {
+            url.push_str(&format!("&data[{}]={}", index, column));
+        }
+        for (name, values) in &self.facets {
+            for value in values {
+                url.push_str(&format!("&facets[{}][]={}", name, value));
+            }
+        }
+        if let Some(start) = &self.start {
+            url.push_str(&format!("&start={}", start));
+        }
+        if let Some(end) = &self.end {
+            url.push_str(&format!("&end={}", end));
+        }
+        // Without a sort the order of rows (and so which rows are on which page) isn't stable
+        url.push_str("&sort[0][column]=period&sort[0][direction]=asc");
+        url.push_str(&format!("&offset={}&length={}", offset, self.length));
+        url
+    }
+
+    /// Every row matching the query, fetching as many pages as it takes
+    pub async fn fetch(&self, api_key: &str, http: &Http) -> Result<Vec<Row>> {
+        fetch_pages(
+            &format!("{} rows from EIA", self.route),
+            |offset| async move {
+                let envelope: Envelope = http
+                    .get_json(&self.url(api_key, offset), &[])
+                    .await
+                    .with_context(|| format!("Could not fetch {} from EIA", self.route))?;
+                let page = envelope.into_page(&self.route)?;
+                let total = number(&page.total).ok_or_else(|| {
+                    anyhow!("Invalid total {} for {} from EIA", page.total, self.route)
+                })?;
+                Ok(collector::Page {
+                    total: total as usize,
+                    records: page.data,
+                })
+            },
+        )
+        .await
+    }
+}
+
+/// One row of a response: the period, every facet, and the requested data columns
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Row {
+    pub period: String,
+    #[serde(flatten)]
+    pub fields: HashMap<String, Value>,
+}
+
+impl Row {
+    /// The value of a facet like "stateId" or "seriesId"
+    pub fn facet(&self, name: &str) -> Option<&str> {
+        self.fields.get(name)?.as_str()
+    }
+
+    /// The number in a data column. The API sends numbers as JSON numbers or strings, and uses
+    /// null or codes like "NA" and "W" (withheld) when there is no value.
+    pub fn value(&self, column: &str) -> Option<f64> {
+        number(self.fields.get(column)?)
+    }
+
+    /// The state this row is for, `None` for the U.S. total and other regions. Routes name the
+    /// facet either "stateId" or "location".
+    pub fn state(&self) -> Option<State> {
+        State::from_code(self.facet("stateId").or_else(|| self.facet("location"))?)
+    }
+
+    /// The year of an annual period
+    pub fn year(&self) -> Result<u16> {
+        self.period
+            .parse()
+            .with_context(|| format!("Invalid year {} from EIA", self.period))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Envelope {
+    response: Option<Page>,
+    error: Option<Value>,
+}
+
+impl Envelope {
+    fn into_page(self, route: &str) -> Result<Page> {
+        match (self.response, self.error) {
+            (Some(page), None) => Ok(page),
+            (_, Some(Value::String(error))) => {
+                Err(anyhow!("EIA rejected the query for {}: {}", route, error))
+            }
+            (_, Some(error)) => Err(anyhow!("EIA rejected the query for {}: {}", route, error)),
+            (None, None) => Err(anyhow!("EIA sent no response for {}", route)),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Page {
+    /// Rows across every page, sent as a number or a string
+    total: Value,
+    data: Vec<Row>,
+}
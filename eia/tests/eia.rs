@@ -0,0 +1,169 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use collector::testing::{collect, fixtures, metric};
+use collector::{AnyCollector, Http, Mode};
+use eia::Eia;
+use states::{Regions, State};
+
+/// The fixtures have 2016 through 2018 for Alaska, DC, Maryland, Wyoming and the U.S. total.
+/// Emissions are the state's FIPS code plus the years since 2016, total consumption is always 100
+/// and renewable consumption is the FIPS code, except Alaska's 2018 renewable consumption which is
+/// withheld. Every state generates 1000 thousand MWh, 300 of it from coal, 400 natural gas, 200
+/// nuclear (with no nuclear row at all in Wyoming), the FIPS code wind and 10 solar, except
+/// Alaska's 2018 solar which is withheld. Population is 100 times the FIPS code in thousands,
+/// except Maryland's which is 6042.718 thousand.
+fn replay(regions: Regions) -> Eia {
+    env::set_var("EIA_KEY", "test");
+    Eia::new(regions, fixtures())
+}
+
+#[tokio::test]
+async fn emissions_and_renewable_share_for_the_latest_year() {
+    let metrics = collect(replay(Regions::States)).await;
+    let (emissions, renewable) = (&metrics[0], &metrics[1]);
+    assert_eq!(emissions.source().source, "co2_emissions.json");
+    assert_eq!(emissions.year(), Some(2018));
+    assert_eq!(emissions.get(State::Maryland), Some(26.0));
+    // Every other state is an explicit null
+    assert_eq!(emissions.values().len(), 50);
+    assert_eq!(emissions.values()[&State::Ohio], None);
+    assert_eq!(emissions.missing()[&State::Ohio], "EIA has no data");
+    assert_eq!(renewable.source().source, "percent_renewable.json");
+    assert_eq!(renewable.get(State::Wyoming), Some(0.56));
+}
+
+#[tokio::test]
+async fn every_metric_is_collected() {
+    let metrics = collect(replay(Regions::States)).await;
+    let files: Vec<&str> = metrics
+        .iter()
+        .map(|metric| metric.source().source.as_ref())
+        .collect();
+    assert_eq!(
+        files,
+        [
+            "co2_emissions.json",
+            "percent_renewable.json",
+            "energy_per_capita.json",
+            "residential_electricity_price.json",
+            "energy_expenditure_per_capita.json",
+            "population.json",
+            "percent_coal.json",
+            "percent_natural_gas.json",
+            "percent_nuclear.json",
+            "percent_wind.json",
+            "percent_solar.json",
+        ]
+    );
+    assert_eq!(
+        metric(&metrics, "energy_per_capita.json").get(State::Maryland),
+        Some(240.0)
+    );
+    assert_eq!(
+        metric(&metrics, "energy_expenditure_per_capita.json").get(State::Maryland),
+        Some(4024.0)
+    );
+}
+
+#[tokio::test]
+async fn generation_shares_are_of_mwh_generated() {
+    let metrics = collect(replay(Regions::States)).await;
+    assert_eq!(
+        metric(&metrics, "percent_coal.json").get(State::Maryland),
+        Some(0.3)
+    );
+    assert_eq!(
+        metric(&metrics, "percent_wind.json").get(State::Wyoming),
+        Some(0.056)
+    );
+    // No row means none was generated
+    let nuclear = metric(&metrics, "percent_nuclear.json");
+    assert_eq!(nuclear.get(State::Wyoming), Some(0.0));
+    assert_eq!(nuclear.year(), Some(2018));
+    // A withheld value leaves the year out
+    let solar = metric(&metrics, "percent_solar.json");
+    assert_eq!(solar.history().unwrap()[&State::Alaska].len(), 2);
+    assert_eq!(solar.year(), Some(2017));
+    assert_eq!(solar.get(State::Alaska), Some(0.01));
+}
+
+#[tokio::test]
+async fn population_is_in_people() {
+    let metrics = collect(replay(Regions::States)).await;
+    let population = metric(&metrics, "population.json");
+    assert_eq!(population.get(State::Wyoming), Some(5_600_000.0));
+    assert_eq!(population.to_json()["MD"], 6_042_718);
+}
+
+#[tokio::test]
+async fn electricity_prices_are_converted_to_cents_per_kwh() {
+    let metrics = collect(replay(Regions::States)).await;
+    let price = metric(&metrics, "residential_electricity_price.json")
+        .get(State::Maryland)
+        .unwrap();
+    // $50 per million Btu
+    assert!((price - 17.06).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn every_year_is_kept_in_the_history() {
+    let metrics = collect(replay(Regions::States)).await;
+    let emissions = &metrics[0];
+    assert_eq!(
+        emissions.source().history.as_deref(),
+        Some("co2_emissions_history.json")
+    );
+    let maryland = &emissions.history().unwrap()[&State::Maryland];
+    assert_eq!(
+        maryland.iter().collect::<Vec<_>>(),
+        [(&2016, &24.0), (&2017, &25.0), (&2018, &26.0)]
+    );
+    let history = emissions.history_to_json().unwrap();
+    assert_eq!(history["MD"]["2017"], 25.0);
+}
+
+#[tokio::test]
+async fn latest_year_is_one_every_state_has() {
+    let metrics = collect(replay(Regions::States)).await;
+    let renewable = &metrics[1];
+    assert_eq!(renewable.year(), Some(2017));
+    assert_eq!(renewable.get(State::Alaska), Some(0.02));
+    assert_eq!(renewable.history().unwrap()[&State::Alaska].len(), 2);
+    assert_eq!(renewable.history().unwrap()[&State::Maryland].len(), 3);
+}
+
+#[tokio::test]
+async fn territories_are_not_covered() {
+    let metrics = collect(replay(Regions::All)).await;
+    assert_eq!(metrics[0].get(State::DistrictOfColumbia), Some(13.0));
+    assert!(!metrics[0].values().contains_key(&State::PuertoRico));
+    assert_eq!(metrics[0].values().len(), 51);
+    assert!(!metrics[0].missing().contains_key(&State::PuertoRico));
+}
+
+#[tokio::test]
+async fn a_failed_query_only_loses_its_own_metrics() {
+    env::set_var("EIA_KEY", "test");
+    let fixtures =
+        Path::new(env!("CARGO_TARGET_TMPDIR")).join("a_failed_query_only_loses_its_own_metrics");
+    let _ = fs::remove_dir_all(&fixtures);
+    fs::create_dir_all(&fixtures).unwrap();
+    for entry in fs::read_dir("tests/fixtures").unwrap() {
+        let path = entry.unwrap().path();
+        if !path.to_string_lossy().contains("seds") {
+            fs::copy(&path, fixtures.join(path.file_name().unwrap())).unwrap();
+        }
+    }
+    let eia = || Eia::new(Regions::States, Http::new(Mode::Replay(fixtures.clone())));
+    let metrics = collect(eia()).await;
+    assert_eq!(metrics.len(), 6);
+    assert_eq!(metrics[0].get(State::Maryland), Some(26.0));
+    assert_eq!(metrics[1].source().source, "percent_coal.json");
+
+    for entry in fs::read_dir(&fixtures).unwrap() {
+        fs::remove_file(entry.unwrap().path()).unwrap();
+    }
+    assert!(eia().collect().await.is_err());
+}
@@ -0,0 +1,114 @@
+use std::path::{Path, PathBuf};
+
+use collector::{save_fixture, Http, Mode};
+use eia::v2::{Query, MAX_LENGTH};
+
+fn query() -> Query {
+    Query::new("seds")
+        .frequency("annual")
+        .data("value")
+        .facet("seriesId", &["TETCB"])
+        .facet("stateId", &["MD", "VA"])
+}
+
+/// A page of `total` rows, one per year starting at `first`
+fn page(total: usize, first: u16, years: u16) -> String {
+    let rows: Vec<String> = (first..first + years)
+        .map(|year| {
+            format!(
+                r#"{{"period":"{}","seriesId":"TETCB","stateId":"MD","value":"{}"}}"#,
+                year, year
+            )
+        })
+        .collect();
+    format!(
+        r#"{{"response":{{"total":"{}","data":[{}]}}}}"#,
+        total,
+        rows.join(",")
+    )
+}
+
+async fn fixtures(name: &str, pages: &[(usize, String)]) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    for (offset, body) in pages {
+        let url = query().length(2).url("test", *offset);
+        save_fixture(&dir, &url, body).await.unwrap();
+    }
+    dir
+}
+
+#[test]
+fn query_urls() {
+    assert_eq!(
+        query().start("2010").url("secret", 0),
+        "https://api.eia.gov/v2/seds/data/?api_key=secret&frequency=annual&data[0]=value\
+         &facets[seriesId][]=TETCB&facets[stateId][]=MD&facets[stateId][]=VA&start=2010\
+         &sort[0][column]=period&sort[0][direction]=asc&offset=0&length=5000"
+    );
+    assert!(query()
+        .length(MAX_LENGTH * 2)
+        .url("secret", 10)
+        .ends_with("&offset=10&length=5000"));
+}
+
+#[tokio::test]
+async fn every_page_is_fetched() {
+    let dir = fixtures(
+        "every_page_is_fetched",
+        &[
+            (0, page(5, 2014, 2)),
+            (2, page(5, 2016, 2)),
+            (4, page(5, 2018, 1)),
+        ],
+    )
+    .await;
+    let http = Http::new(Mode::Replay(dir));
+    let rows = query().length(2).fetch("test", &http).await.unwrap();
+    let years: Vec<u16> = rows.iter().map(|row| row.year().unwrap()).collect();
+    assert_eq!(years, [2014, 2015, 2016, 2017, 2018]);
+    assert_eq!(rows[0].value("value"), Some(2014.0));
+    assert_eq!(rows[0].facet("stateId"), Some("MD"));
+    assert_eq!(rows[0].state(), Some(states::State::Maryland));
+}
+
+#[tokio::test]
+async fn missing_values_are_none() {
+    let body = r#"{"response":{"total":3,"data":[
+        {"period":"2016","stateId":"US","value":null},
+        {"period":"2017","stateId":"MD","value":"W"},
+        {"period":"2018","stateId":"MD","value":1.5}
+    ]}}"#;
+    let dir = fixtures("missing_values_are_none", &[(0, body.to_owned())]).await;
+    let http = Http::new(Mode::Replay(dir));
+    let rows = query().length(2).fetch("test", &http).await.unwrap();
+    let values: Vec<Option<f64>> = rows.iter().map(|row| row.value("value")).collect();
+    assert_eq!(values, [None, None, Some(1.5)]);
+    assert_eq!(rows[0].state(), None);
+}
+
+#[tokio::test]
+async fn states_come_from_either_facet() {
+    let body = r#"{"response":{"total":2,"data":[
+        {"period":"2018","stateId":"MD","value":1},
+        {"period":"2018","location":"WY","generation":2}
+    ]}}"#;
+    let dir = fixtures("states_come_from_either_facet", &[(0, body.to_owned())]).await;
+    let http = Http::new(Mode::Replay(dir));
+    let rows = query().length(2).fetch("test", &http).await.unwrap();
+    assert_eq!(rows[0].state(), Some(states::State::Maryland));
+    assert_eq!(rows[1].state(), Some(states::State::Wyoming));
+    assert_eq!(rows[1].value("generation"), Some(2.0));
+}
+
+#[tokio::test]
+async fn errors_from_the_api_are_reported() {
+    let body = r#"{"error":"Invalid facet 'stateId'","code":400}"#;
+    let dir = fixtures("errors_from_the_api_are_reported", &[(0, body.to_owned())]).await;
+    let http = Http::new(Mode::Replay(dir));
+    let error = query().length(2).fetch("test", &http).await.unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "EIA rejected the query for seds: Invalid facet 'stateId'"
+    );
+}
@@ -1,19 +1,84 @@
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::str::FromStr;
 
 use anyhow::{anyhow, Result};
 use serde::Deserialize;
-use tokio::fs;
 
-use states::STATES_BY_NAME;
+use collector::Http;
+use metrics::{DataType, Metric, Sort, Source};
+use states::{Regions, Resolver, State};
 
 use crate::{parse_percent, BASE_URL};
-use futures::future::try_join_all;
-use std::str::FromStr;
 
 static PATH: &str = "8mc4-hxm7.json";
+static SOURCE_URL: &str = "https://opendata.maryland.gov/Demographic/Choose-Maryland-Compare-States-Demographics/8mc4-hxm7";
+
+static POP_CHANGE: Source = Source {
+    source: Cow::Borrowed("pop_change.json"),
+    name: Cow::Borrowed("Population Change"),
+    data_type: DataType::Percent,
+    source_url: Cow::Borrowed(SOURCE_URL),
+    description: Cow::Borrowed("Population change over one year."),
+    sort: Sort::Descending,
+    ..Source::BASE
+};
+
+static MEDIAN_AGE: Source = Source {
+    source: Cow::Borrowed("median_age.json"),
+    name: Cow::Borrowed("Median Age"),
+    data_type: DataType::Number,
+    source_url: Cow::Borrowed(SOURCE_URL),
+    description: Cow::Borrowed("Median Age."),
+    units: Some(Cow::Borrowed("years")),
+    sort: Sort::Descending,
+    ..Source::BASE
+};
+
+static POP_DENSITY: Source = Source {
+    source: Cow::Borrowed("pop_density.json"),
+    name: Cow::Borrowed("Population Density"),
+    data_type: DataType::Number,
+    source_url: Cow::Borrowed(SOURCE_URL),
+    description: Cow::Borrowed("Population per square mile of land area."),
+    units: Some(Cow::Borrowed("/mi^2")),
+    sort: Sort::Descending,
+    ..Source::BASE
+};
+
+static MEDIAN_HOUSEHOLD_INCOME: Source = Source {
+    source: Cow::Borrowed("median_household_income.json"),
+    name: Cow::Borrowed("Median Household Income"),
+    data_type: DataType::Money,
+    source_url: Cow::Borrowed(SOURCE_URL),
+    description: Cow::Borrowed("Median household income in 2018 inflation-adjusted dollars."),
+    units: Some(Cow::Borrowed("$")),
+    sort: Sort::Descending,
+    ..Source::BASE
+};
+
+static PERCAPITA_PERSONAL_INCOME: Source = Source {
+    source: Cow::Borrowed("percapita_personal_income.json"),
+    name: Cow::Borrowed("Per Capita Income"),
+    data_type: DataType::Money,
+    source_url: Cow::Borrowed(SOURCE_URL),
+    description: Cow::Borrowed("Income received before the deduction of taxes."),
+    units: Some(Cow::Borrowed("$")),
+    sort: Sort::Descending,
+    ..Source::BASE
+};
+
+static POVERTY_RATE: Source = Source {
+    source: Cow::Borrowed("poverty_rate.json"),
+    name: Cow::Borrowed("Poverty Rate"),
+    data_type: DataType::Percent,
+    source_url: Cow::Borrowed(SOURCE_URL),
+    description: Cow::Borrowed("Percent of people below the poverty level."),
+    sort: Sort::Ascending,
+    ..Source::BASE
+};
 
 #[derive(Debug, Deserialize)]
-struct DemoData {
+pub(crate) struct DemoData {
     state: String,
     pop_perc_change_years1: Option<String>,
     median_age: Option<String>,
@@ -25,7 +90,7 @@ struct DemoData {
 
 #[derive(Debug)]
 struct DemoParsed {
-    state: &'static str,
+    state: State,
     pop_change: f64,
     median_age: f64,
     pop_density: f64,
@@ -35,9 +100,9 @@ struct DemoParsed {
 }
 
 impl DemoParsed {
-    fn try_from(value: DemoData) -> Result<Self> {
-        let state = *STATES_BY_NAME
-            .get(value.state.as_str())
+    fn try_from(value: DemoData, resolver: &mut Resolver) -> Result<Self> {
+        let state = resolver
+            .resolve(&value.state)
             .ok_or_else(|| anyhow!("Invalid state"))?;
         let pop_change = parse_percent(
             &value
@@ -65,69 +130,55 @@ impl DemoParsed {
     }
 }
 
-pub async fn demographic() -> Result<()> {
-    let data = get_demographic().await?;
-    let pop_change: HashMap<&str, f64> = data
-        .iter()
-        .map(|value| (value.state, value.pop_change))
-        .collect();
-    let median_age: HashMap<&str, f64> = data
-        .iter()
-        .map(|value| (value.state, value.median_age))
-        .collect();
-    let pop_density: HashMap<&str, f64> = data
-        .iter()
-        .map(|value| (value.state, value.pop_density))
-        .collect();
-    let median_household_income: HashMap<&str, f64> = data
-        .iter()
-        .map(|value| (value.state, value.median_household_income))
-        .collect();
-    let percapita_personal_income: HashMap<&str, f64> = data
-        .iter()
-        .map(|value| (value.state, value.percapita_personal_income))
-        .collect();
-    let poverty_rate: HashMap<&str, f64> = data
-        .iter()
-        .map(|value| (value.state, value.poverty_rate))
-        .collect();
-    try_join_all(vec![
-        fs::write(
-            "generated/pop_change.json",
-            serde_json::to_string(&pop_change)?,
+pub(crate) fn sources() -> Vec<Source> {
+    vec![
+        POP_CHANGE.clone(),
+        MEDIAN_AGE.clone(),
+        POP_DENSITY.clone(),
+        MEDIAN_HOUSEHOLD_INCOME.clone(),
+        PERCAPITA_PERSONAL_INCOME.clone(),
+        POVERTY_RATE.clone(),
+    ]
+}
+
+pub(crate) async fn fetch(http: &Http) -> Result<Vec<DemoData>> {
+    http.get_json(&format!("{}{}", BASE_URL, PATH), &[]).await
+}
+
+pub(crate) fn parse(data: Vec<DemoData>, regions: Regions) -> Vec<Metric> {
+    let mut resolver = Resolver::new();
+    let data = data
+        .into_iter()
+        .filter_map(|data| DemoParsed::try_from(data, &mut resolver).ok())
+        .filter(|parsed| regions.contains(parsed.state))
+        .collect::<Vec<DemoParsed>>();
+    resolver.warn_unresolved(&format!("{}{}", BASE_URL, PATH));
+    vec![
+        Metric::new(
+            POP_CHANGE.clone(),
+            data.iter().map(|value| (value.state, value.pop_change)),
         ),
-        fs::write(
-            "generated/median_age.json",
-            serde_json::to_string(&median_age)?,
+        Metric::new(
+            MEDIAN_AGE.clone(),
+            data.iter().map(|value| (value.state, value.median_age)),
         ),
-        fs::write(
-            "generated/pop_density.json",
-            serde_json::to_string(&pop_density)?,
+        Metric::new(
+            POP_DENSITY.clone(),
+            data.iter().map(|value| (value.state, value.pop_density)),
         ),
-        fs::write(
-            "generated/median_household_income.json",
-            serde_json::to_string(&median_household_income)?,
+        Metric::new(
+            MEDIAN_HOUSEHOLD_INCOME.clone(),
+            data.iter()
+                .map(|value| (value.state, value.median_household_income)),
         ),
-        fs::write(
-            "generated/percapita_personal_income.json",
-            serde_json::to_string(&percapita_personal_income)?,
+        Metric::new(
+            PERCAPITA_PERSONAL_INCOME.clone(),
+            data.iter()
+                .map(|value| (value.state, value.percapita_personal_income)),
         ),
-        fs::write(
-            "generated/poverty_rate.json",
-            serde_json::to_string(&poverty_rate)?,
+        Metric::new(
+            POVERTY_RATE.clone(),
+            data.iter().map(|value| (value.state, value.poverty_rate)),
         ),
-    ])
-    .await?;
-    Ok(())
-}
-
-async fn get_demographic() -> Result<Vec<DemoParsed>> {
-    let uri = format!("{}{}", BASE_URL, PATH);
-    Ok(reqwest::get(&uri)
-        .await?
-        .json::<Vec<DemoData>>()
-        .await?
-        .into_iter()
-        .filter_map(|data| DemoParsed::try_from(data).ok())
-        .collect::<Vec<DemoParsed>>())
+    ]
 }
@@ -1,11 +1,14 @@
+#![forbid(unsafe_code)]
+
 use std::str::FromStr;
 
 use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::future::try_join4;
 
-pub use demographic::demographic;
-pub use quality_of_life::quality_of_life;
-pub use taxes::taxes;
-pub use workforce::workforce;
+use collector::{Collector, Http};
+use metrics::{Metric, Source};
+use states::Regions;
 
 mod demographic;
 mod quality_of_life;
@@ -14,12 +17,71 @@ mod workforce;
 
 pub static BASE_URL: &str = "https://opendata.maryland.gov/resource/";
 
+/// The rows of every "Choose Maryland" data set, as downloaded
+pub struct Raw {
+    taxes: Vec<taxes::TaxData>,
+    workforce: Vec<workforce::WorkforceData>,
+    demographic: Vec<demographic::DemoData>,
+    quality_of_life: Vec<quality_of_life::QOLData>,
+}
+
+/// Every "Choose Maryland" data set for the states in `regions`
+pub struct Maryland {
+    regions: Regions,
+    http: Http,
+}
+
+impl Maryland {
+    pub fn new(regions: Regions, http: Http) -> Self {
+        Self { regions, http }
+    }
+}
+
+#[async_trait]
+impl Collector for Maryland {
+    type Raw = Raw;
+
+    fn name(&self) -> &'static str {
+        "maryland"
+    }
+
+    fn sources(&self) -> Vec<Source> {
+        taxes::sources()
+            .into_iter()
+            .chain(workforce::sources())
+            .chain(demographic::sources())
+            .chain(quality_of_life::sources())
+            .collect()
+    }
+
+    async fn fetch(&self) -> Result<Self::Raw> {
+        let (taxes, workforce, demographic, quality_of_life) = try_join4(
+            taxes::fetch(&self.http),
+            workforce::fetch(&self.http),
+            demographic::fetch(&self.http),
+            quality_of_life::fetch(&self.http),
+        )
+        .await?;
+        Ok(Raw {
+            taxes,
+            workforce,
+            demographic,
+            quality_of_life,
+        })
+    }
+
+    fn parse(&self, raw: Self::Raw) -> Result<Vec<Metric>> {
+        Ok(taxes::parse(raw.taxes, self.regions)
+            .into_iter()
+            .chain(workforce::parse(raw.workforce, self.regions))
+            .chain(demographic::parse(raw.demographic, self.regions))
+            .chain(quality_of_life::parse(raw.quality_of_life, self.regions))
+            .collect())
+    }
+}
+
 pub fn parse_percent(val: &str) -> Result<f64> {
-    let no_percent = if val.ends_with('%') {
-        &val[..val.len() - 1]
-    } else {
-        val
-    };
+    let no_percent = val.strip_suffix('%').unwrap_or(val);
     f64::from_str(no_percent)
         .map(|f| f / 100.0)
         .map_err(|e| anyhow!(e))
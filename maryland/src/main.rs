@@ -1,9 +1,11 @@
 #![forbid(unsafe_code)]
 
 use anyhow::Result;
-use futures::future::try_join4;
 
-use maryland::{demographic, quality_of_life, taxes, workforce};
+use collector::{AnyCollector, Http};
+use maryland::Maryland;
+use metrics::Output;
+use states::Regions;
 
 #[tokio::main]
 async fn main() {
@@ -14,6 +16,8 @@ async fn main() {
 }
 
 async fn run() -> Result<()> {
-    try_join4(taxes(), workforce(), demographic(), quality_of_life()).await?;
-    Ok(())
+    let metrics = Maryland::new(Regions::from_env()?, Http::from_env()?)
+        .collect()
+        .await?;
+    Output::default().write(&metrics)
 }
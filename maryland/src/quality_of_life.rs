@@ -1,31 +1,42 @@
-use std::collections::HashMap;
+use std::borrow::Cow;
 
 use anyhow::{anyhow, Result};
 use serde::Deserialize;
-use tokio::fs;
 
-use states::STATES_BY_NAME;
+use collector::Http;
+use metrics::{DataType, Metric, Sort, Source};
+use states::{Regions, Resolver, State};
 
 use crate::{parse_percent, BASE_URL};
 
 static PATH: &str = "cz6x-aq2i.json";
 
+static BROADBAND_INTERNET: Source = Source {
+    source: Cow::Borrowed("broadband_internet.json"),
+    name: Cow::Borrowed("Internet Coverage"),
+    data_type: DataType::Percent,
+    source_url: Cow::Borrowed("https://opendata.maryland.gov/Health-and-Human-Services/Choose-Maryland-Compare-States-Quality-Of-Life/cz6x-aq2i"),
+    description: Cow::Borrowed("Percent of population with access to broadband internet."),
+    sort: Sort::Descending,
+    ..Source::BASE
+};
+
 #[derive(Debug, Deserialize)]
-struct QOLData {
+pub(crate) struct QOLData {
     state: String,
     broadband_internet: Option<String>,
 }
 
 #[derive(Debug)]
 struct QOLParsed {
-    state: &'static str,
+    state: State,
     broadband_internet: f64,
 }
 
 impl QOLParsed {
-    fn try_from(value: QOLData) -> Result<Self> {
-        let state = *STATES_BY_NAME
-            .get(value.state.as_str())
+    fn try_from(value: QOLData, resolver: &mut Resolver) -> Result<Self> {
+        let state = resolver
+            .resolve(&value.state)
             .ok_or_else(|| anyhow!("Invalid state"))?;
         let broadband_internet = parse_percent(
             &value
@@ -39,27 +50,25 @@ impl QOLParsed {
     }
 }
 
-pub async fn quality_of_life() -> Result<()> {
-    let data = get_qol().await?;
-    let broadband_internet: HashMap<&str, f64> = data
-        .iter()
-        .map(|value| (value.state, value.broadband_internet))
-        .collect();
-    fs::write(
-        "generated/broadband_internet.json",
-        serde_json::to_string(&broadband_internet)?,
-    )
-    .await?;
-    Ok(())
+pub(crate) fn sources() -> Vec<Source> {
+    vec![BROADBAND_INTERNET.clone()]
+}
+
+pub(crate) async fn fetch(http: &Http) -> Result<Vec<QOLData>> {
+    http.get_json(&format!("{}{}", BASE_URL, PATH), &[]).await
 }
 
-async fn get_qol() -> Result<Vec<QOLParsed>> {
-    let uri = format!("{}{}", BASE_URL, PATH);
-    Ok(reqwest::get(&uri)
-        .await?
-        .json::<Vec<QOLData>>()
-        .await?
+pub(crate) fn parse(data: Vec<QOLData>, regions: Regions) -> Vec<Metric> {
+    let mut resolver = Resolver::new();
+    let data = data
         .into_iter()
-        .filter_map(|data| QOLParsed::try_from(data).ok())
-        .collect::<Vec<QOLParsed>>())
+        .filter_map(|data| QOLParsed::try_from(data, &mut resolver).ok())
+        .filter(|parsed| regions.contains(parsed.state))
+        .collect::<Vec<QOLParsed>>();
+    resolver.warn_unresolved(&format!("{}{}", BASE_URL, PATH));
+    vec![Metric::new(
+        BROADBAND_INTERNET.clone(),
+        data.iter()
+            .map(|value| (value.state, value.broadband_internet)),
+    )]
 }
@@ -1,18 +1,50 @@
-use std::collections::HashMap;
+use std::borrow::Cow;
 
 use anyhow::{anyhow, Result};
-use futures::future::try_join3;
 use serde::Deserialize;
-use tokio::fs;
 
-use states::STATES_BY_NAME;
+use collector::Http;
+use metrics::{DataType, Metric, Sort, Source};
+use states::{Regions, Resolver, State};
 
 use crate::{parse_percent, BASE_URL};
 
 static PATH: &str = "t833-r94z.json";
+static SOURCE_URL: &str =
+    "https://catalog.data.gov/dataset/choose-maryland-compare-states-taxes-0f1f5";
+
+static INCOME_TAX: Source = Source {
+    source: Cow::Borrowed("income_tax.json"),
+    name: Cow::Borrowed("Income Tax"),
+    data_type: DataType::Percent,
+    source_url: Cow::Borrowed(SOURCE_URL),
+    description: Cow::Borrowed("Personal income tax rate."),
+    sort: Sort::Ascending,
+    ..Source::BASE
+};
+
+static CORPORATE_INCOME_TAX: Source = Source {
+    source: Cow::Borrowed("corporate_income_tax.json"),
+    name: Cow::Borrowed("Corporate Income Tax"),
+    data_type: DataType::Percent,
+    source_url: Cow::Borrowed(SOURCE_URL),
+    description: Cow::Borrowed("Corporate income tax rate."),
+    sort: Sort::Ascending,
+    ..Source::BASE
+};
+
+static SALES_TAX: Source = Source {
+    source: Cow::Borrowed("sales_tax.json"),
+    name: Cow::Borrowed("Sales Tax"),
+    data_type: DataType::Percent,
+    source_url: Cow::Borrowed(SOURCE_URL),
+    description: Cow::Borrowed("Sales income tax rate."),
+    sort: Sort::Ascending,
+    ..Source::BASE
+};
 
 #[derive(Debug, Deserialize)]
-struct TaxData {
+pub(crate) struct TaxData {
     state: String,
     state_individual_income_taxrate: String,
     state_corporate_income_taxrate: String,
@@ -21,16 +53,16 @@ struct TaxData {
 
 #[derive(Debug)]
 struct TaxDataParsed {
-    state: &'static str,
+    state: State,
     income_tax: f64,
     corp_income_tax: f64,
     sales_tax: f64,
 }
 
 impl TaxDataParsed {
-    fn try_from(value: TaxData) -> Result<Self> {
-        let state = *STATES_BY_NAME
-            .get(value.state.as_str())
+    fn try_from(value: TaxData, resolver: &mut Resolver) -> Result<Self> {
+        let state = resolver
+            .resolve(&value.state)
             .ok_or_else(|| anyhow!("Invalid state"))?;
         let income_tax = parse_percent(&value.state_individual_income_taxrate)?;
         let corp_income_tax = parse_percent(&value.state_corporate_income_taxrate)?;
@@ -44,45 +76,39 @@ impl TaxDataParsed {
     }
 }
 
-pub async fn taxes() -> Result<()> {
-    let data = get_taxes().await?;
-    let income_tax: HashMap<&str, f64> = data
-        .iter()
-        .map(|value| (value.state, value.income_tax))
-        .collect();
-    let corp_tax: HashMap<&str, f64> = data
-        .iter()
-        .map(|value| (value.state, value.corp_income_tax))
-        .collect();
-    let sales_tax: HashMap<&str, f64> = data
-        .iter()
-        .map(|value| (value.state, value.sales_tax))
-        .collect();
-    try_join3(
-        fs::write(
-            "generated/income_tax.json",
-            serde_json::to_string(&income_tax)?,
-        ),
-        fs::write(
-            "generated/corporate_income_tax.json",
-            serde_json::to_string(&corp_tax)?,
-        ),
-        fs::write(
-            "generated/sales_tax.json",
-            serde_json::to_string(&sales_tax)?,
-        ),
-    )
-    .await?;
-    Ok(())
+pub(crate) fn sources() -> Vec<Source> {
+    vec![
+        INCOME_TAX.clone(),
+        CORPORATE_INCOME_TAX.clone(),
+        SALES_TAX.clone(),
+    ]
 }
 
-async fn get_taxes() -> Result<Vec<TaxDataParsed>> {
-    let uri = format!("{}{}", BASE_URL, PATH);
-    Ok(reqwest::get(&uri)
-        .await?
-        .json::<Vec<TaxData>>()
-        .await?
+pub(crate) async fn fetch(http: &Http) -> Result<Vec<TaxData>> {
+    http.get_json(&format!("{}{}", BASE_URL, PATH), &[]).await
+}
+
+pub(crate) fn parse(data: Vec<TaxData>, regions: Regions) -> Vec<Metric> {
+    let mut resolver = Resolver::new();
+    let data = data
         .into_iter()
-        .filter_map(|data| TaxDataParsed::try_from(data).ok())
-        .collect::<Vec<TaxDataParsed>>())
+        .filter_map(|data| TaxDataParsed::try_from(data, &mut resolver).ok())
+        .filter(|parsed| regions.contains(parsed.state))
+        .collect::<Vec<TaxDataParsed>>();
+    resolver.warn_unresolved(&format!("{}{}", BASE_URL, PATH));
+    vec![
+        Metric::new(
+            INCOME_TAX.clone(),
+            data.iter().map(|value| (value.state, value.income_tax)),
+        ),
+        Metric::new(
+            CORPORATE_INCOME_TAX.clone(),
+            data.iter()
+                .map(|value| (value.state, value.corp_income_tax)),
+        ),
+        Metric::new(
+            SALES_TAX.clone(),
+            data.iter().map(|value| (value.state, value.sales_tax)),
+        ),
+    ]
 }
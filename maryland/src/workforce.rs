@@ -1,31 +1,42 @@
-use std::collections::HashMap;
+use std::borrow::Cow;
 
 use anyhow::{anyhow, Result};
 use serde::Deserialize;
-use tokio::fs;
 
-use states::STATES_BY_NAME;
+use collector::Http;
+use metrics::{DataType, Metric, Sort, Source};
+use states::{Regions, Resolver, State};
 
 use crate::{parse_percent, BASE_URL};
 
 static PATH: &str = "5esm-neyf.json";
 
+static UNEMPLOYMENT: Source = Source {
+    source: Cow::Borrowed("unemployment.json"),
+    name: Cow::Borrowed("Unemployment"),
+    data_type: DataType::Percent,
+    source_url: Cow::Borrowed("https://opendata.maryland.gov/Business-and-Economy/Choose-Maryland-Compare-States-Workforce/5esm-neyf"),
+    description: Cow::Borrowed("Unemployment rate."),
+    sort: Sort::Ascending,
+    ..Source::BASE
+};
+
 #[derive(Debug, Deserialize)]
-struct WorkforceData {
+pub(crate) struct WorkforceData {
     state: String,
     unemployment_rate: Option<String>,
 }
 
 #[derive(Debug)]
 struct WorkforceParsed {
-    state: &'static str,
+    state: State,
     unemployment: f64,
 }
 
 impl WorkforceParsed {
-    fn try_from(value: WorkforceData) -> Result<Self> {
-        let state = *STATES_BY_NAME
-            .get(value.state.as_str())
+    fn try_from(value: WorkforceData, resolver: &mut Resolver) -> Result<Self> {
+        let state = resolver
+            .resolve(&value.state)
             .ok_or_else(|| anyhow!("Invalid state"))?;
         let unemployment = parse_percent(
             &value
@@ -39,27 +50,24 @@ impl WorkforceParsed {
     }
 }
 
-pub async fn workforce() -> Result<()> {
-    let data = get_workforce().await?;
-    let unemployment: HashMap<&str, f64> = data
-        .iter()
-        .map(|value| (value.state, value.unemployment))
-        .collect();
-    fs::write(
-        "generated/unemployment.json",
-        serde_json::to_string(&unemployment)?,
-    )
-    .await?;
-    Ok(())
+pub(crate) fn sources() -> Vec<Source> {
+    vec![UNEMPLOYMENT.clone()]
+}
+
+pub(crate) async fn fetch(http: &Http) -> Result<Vec<WorkforceData>> {
+    http.get_json(&format!("{}{}", BASE_URL, PATH), &[]).await
 }
 
-async fn get_workforce() -> Result<Vec<WorkforceParsed>> {
-    let uri = format!("{}{}", BASE_URL, PATH);
-    Ok(reqwest::get(&uri)
-        .await?
-        .json::<Vec<WorkforceData>>()
-        .await?
+pub(crate) fn parse(data: Vec<WorkforceData>, regions: Regions) -> Vec<Metric> {
+    let mut resolver = Resolver::new();
+    let data = data
         .into_iter()
-        .filter_map(|data| WorkforceParsed::try_from(data).ok())
-        .collect::<Vec<WorkforceParsed>>())
+        .filter_map(|data| WorkforceParsed::try_from(data, &mut resolver).ok())
+        .filter(|parsed| regions.contains(parsed.state))
+        .collect::<Vec<WorkforceParsed>>();
+    resolver.warn_unresolved(&format!("{}{}", BASE_URL, PATH));
+    vec![Metric::new(
+        UNEMPLOYMENT.clone(),
+        data.iter().map(|value| (value.state, value.unemployment)),
+    )]
 }
@@ -0,0 +1,68 @@
+use collector::testing::{collect, fixtures, metric};
+use maryland::Maryland;
+use metrics::Metric;
+use states::{Regions, State};
+
+fn value(metrics: &[Metric], file: &str, state: State) -> Option<f64> {
+    metric(metrics, file).get(state)
+}
+
+fn assert_close(actual: Option<f64>, expected: f64) {
+    let actual = actual.unwrap();
+    assert!(
+        (actual - expected).abs() < 1e-9,
+        "expected {}, got {}",
+        expected,
+        actual
+    );
+}
+
+#[tokio::test]
+async fn parses_every_data_set() {
+    let metrics = collect(Maryland::new(Regions::States, fixtures())).await;
+    assert_eq!(metrics.len(), 11);
+    assert_close(value(&metrics, "income_tax.json", State::Maryland), 0.0575);
+    assert_close(
+        value(&metrics, "corporate_income_tax.json", State::Virginia),
+        0.06,
+    );
+    assert_close(value(&metrics, "sales_tax.json", State::Texas), 0.0625);
+    assert_close(value(&metrics, "unemployment.json", State::Virginia), 0.029);
+    assert_close(value(&metrics, "pop_change.json", State::Maryland), 0.004);
+    assert_close(value(&metrics, "median_age.json", State::Virginia), 38.3);
+    assert_close(value(&metrics, "pop_density.json", State::Maryland), 622.8);
+    assert_close(
+        value(&metrics, "median_household_income.json", State::Maryland),
+        83242.0,
+    );
+    assert_close(
+        value(&metrics, "percapita_personal_income.json", State::Virginia),
+        56920.0,
+    );
+    assert_close(value(&metrics, "poverty_rate.json", State::Virginia), 0.107);
+    assert_close(
+        value(&metrics, "broadband_internet.json", State::Texas),
+        0.779,
+    );
+}
+
+#[tokio::test]
+async fn skips_rows_without_a_value() {
+    let metrics = collect(Maryland::new(Regions::States, fixtures())).await;
+    assert_eq!(value(&metrics, "unemployment.json", State::Texas), None);
+}
+
+#[tokio::test]
+async fn dc_follows_regions() {
+    let states = collect(Maryland::new(Regions::States, fixtures())).await;
+    assert_eq!(
+        value(&states, "income_tax.json", State::DistrictOfColumbia),
+        None
+    );
+
+    let with_dc = collect(Maryland::new(Regions::StatesAndDc, fixtures())).await;
+    assert_close(
+        value(&with_dc, "income_tax.json", State::DistrictOfColumbia),
+        0.0895,
+    );
+}
@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use states::Regions;
+
+use crate::{Metric, Source};
+
+/// One entry in derived.json: everything about a metric plus the expression which computes it
+/// from other generated metrics, like `"summer_temp - winter_temp"`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Derived {
+    #[serde(flatten)]
+    pub source: Source,
+    pub expression: String,
+}
+
+impl Derived {
+    /// Every entry in a derived.json file, in order
+    pub fn read_all(path: &Path) -> Result<Vec<Derived>> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Could not read {}", path.display()))?;
+        serde_json::from_str(&contents).with_context(|| format!("Invalid {}", path.display()))
+    }
+
+    /// The parsed expression
+    pub fn expression(&self) -> Result<Expression> {
+        Expression::parse(&self.expression)
+            .with_context(|| format!("Invalid expression for {}", self.source.source))
+    }
+
+    /// Evaluate the expression for every state in `regions`. `inputs` are the metrics named in
+    /// the expression, by file. A state is `null` if any input is `null` or missing for it, or the
+    /// expression divides by 0.
+    pub fn evaluate(&self, inputs: &HashMap<String, Metric>, regions: Regions) -> Result<Metric> {
+        let expression = self.expression()?;
+        for file in expression.files() {
+            if !inputs.contains_key(&file) {
+                return Err(anyhow!("{} needs {}", self.source.source, file));
+            }
+        }
+        let values = regions.iter().map(|state| {
+            let value = expression.evaluate(&|file| inputs.get(file)?.get(state));
+            (state, value)
+        });
+        Ok(Metric::new(self.source.clone(), values))
+    }
+}
+
+/// Arithmetic over generated metrics: numbers, `+ - * /`, parentheses, and names of files in
+/// `generated` without the ".json", which stand for each state's value in that file.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expression {
+    Number(f64),
+    /// A generated file, e.g. "summer_temp.json"
+    Metric(String),
+    Negate(Box<Expression>),
+    Binary(Box<Expression>, Operator, Box<Expression>),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+impl Expression {
+    pub fn parse(expression: &str) -> Result<Self> {
+        let tokens = tokenize(expression)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            position: 0,
+        };
+        let parsed = parser.sum()?;
+        match parser.peek() {
+            None => Ok(parsed),
+            Some(token) => Err(anyhow!("unexpected {}", token)),
+        }
+    }
+
+    /// Every file the expression reads, once each
+    pub fn files(&self) -> Vec<String> {
+        let mut files = Vec::new();
+        self.collect_files(&mut files);
+        files
+    }
+
+    fn collect_files(&self, files: &mut Vec<String>) {
+        match self {
+            Expression::Number(_) => {}
+            Expression::Metric(file) => {
+                if !files.contains(file) {
+                    files.push(file.clone());
+                }
+            }
+            Expression::Negate(inner) => inner.collect_files(files),
+            Expression::Binary(left, _, right) => {
+                left.collect_files(files);
+                right.collect_files(files);
+            }
+        }
+    }
+
+    /// The value with every metric looked up in `value`, `None` if any of them are or if it
+    /// divides by 0
+    pub fn evaluate(&self, value: &dyn Fn(&str) -> Option<f64>) -> Option<f64> {
+        match self {
+            Expression::Number(number) => Some(*number),
+            Expression::Metric(file) => value(file),
+            Expression::Negate(inner) => Some(-inner.evaluate(value)?),
+            Expression::Binary(left, operator, right) => {
+                let (left, right) = (left.evaluate(value)?, right.evaluate(value)?);
+                match operator {
+                    Operator::Add => Some(left + right),
+                    Operator::Subtract => Some(left - right),
+                    Operator::Multiply => Some(left * right),
+                    Operator::Divide if right == 0.0 => None,
+                    Operator::Divide => Some(left / right),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Symbol(char),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Number(number) => write!(f, "{}", number),
+            Token::Name(name) => f.write_str(name),
+            Token::Symbol(symbol) => write!(f, "\"{}\"", symbol),
+        }
+    }
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut rest = expression;
+    while let Some(c) = rest.chars().next() {
+        // How many bytes of `rest` the token takes up
+        let length = match c {
+            c if c.is_whitespace() => c.len_utf8(),
+            '+' | '-' | '*' | '/' | '(' | ')' => {
+                tokens.push(Token::Symbol(c));
+                1
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let length = rest
+                    .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '_'))
+                    .unwrap_or(rest.len());
+                let number = &rest[..length];
+                let parsed = number
+                    .replace('_', "")
+                    .parse()
+                    .map_err(|_| anyhow!("invalid number {}", number))?;
+                tokens.push(Token::Number(parsed));
+                length
+            }
+            c if c.is_ascii_lowercase() || c == '_' => {
+                let length = rest
+                    .find(|c: char| !(c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_'))
+                    .unwrap_or(rest.len());
+                tokens.push(Token::Name(rest[..length].to_owned()));
+                length
+            }
+            _ => return Err(anyhow!("unexpected \"{}\"", c)),
+        };
+        rest = &rest[length..];
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent, where each method parses one level of precedence
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        token
+    }
+
+    /// Terms joined by + and -
+    fn sum(&mut self) -> Result<Expression> {
+        let mut left = self.product()?;
+        loop {
+            let operator = match self.peek() {
+                Some(Token::Symbol('+')) => Operator::Add,
+                Some(Token::Symbol('-')) => Operator::Subtract,
+                _ => return Ok(left),
+            };
+            self.position += 1;
+            left = Expression::Binary(Box::new(left), operator, Box::new(self.product()?));
+        }
+    }
+
+    /// Factors joined by * and /
+    fn product(&mut self) -> Result<Expression> {
+        let mut left = self.factor()?;
+        loop {
+            let operator = match self.peek() {
+                Some(Token::Symbol('*')) => Operator::Multiply,
+                Some(Token::Symbol('/')) => Operator::Divide,
+                _ => return Ok(left),
+            };
+            self.position += 1;
+            left = Expression::Binary(Box::new(left), operator, Box::new(self.factor()?));
+        }
+    }
+
+    /// A number, a metric, a negated factor, or a parenthesized sum
+    fn factor(&mut self) -> Result<Expression> {
+        match self.next().cloned() {
+            Some(Token::Number(number)) => Ok(Expression::Number(number)),
+            Some(Token::Name(name)) => Ok(Expression::Metric(format!("{}.json", name))),
+            Some(Token::Symbol('-')) => Ok(Expression::Negate(Box::new(self.factor()?))),
+            Some(Token::Symbol('(')) => {
+                let inner = self.sum()?;
+                match self.next() {
+                    Some(Token::Symbol(')')) => Ok(inner),
+                    Some(token) => Err(anyhow!("expected \")\", found {}", token)),
+                    None => Err(anyhow!("expected \")\"")),
+                }
+            }
+            Some(token) => Err(anyhow!("unexpected {}", token)),
+            None => Err(anyhow!("unexpected end")),
+        }
+    }
+}
@@ -0,0 +1,20 @@
+#![forbid(unsafe_code)]
+
+pub use derive::{Derived, Expression, Operator};
+pub use metric::{History, Item, Items, Metric, Months, Point};
+pub use normalize::{land_area, Normalize, Per};
+pub use output::Output;
+pub use score::{Profile, Scaling, Weight};
+pub use source::{DataType, Sort, Source};
+pub use stats::Stats;
+pub use validate::{validate, validate_file, Problem};
+
+mod derive;
+mod metric;
+mod normalize;
+mod output;
+mod score;
+mod side_file;
+mod source;
+mod stats;
+mod validate;
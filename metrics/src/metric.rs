@@ -0,0 +1,338 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use states::State;
+
+use crate::{DataType, Source, Stats};
+
+/// Every year's value for each state
+pub type History = BTreeMap<State, BTreeMap<u16, f64>>;
+
+/// Each state's value for every month, January first
+pub type Months = BTreeMap<State, [Option<f64>; 12]>;
+
+/// One of the places behind a state's value, e.g. a weather station
+#[derive(Clone, Debug, PartialEq)]
+pub struct Point {
+    /// Unique within the points file
+    pub id: String,
+    pub name: String,
+    pub state: State,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// In meters, if known
+    pub elevation: Option<f64>,
+    pub value: f64,
+}
+
+/// One of the things behind a state's value, e.g. a park counted in it
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Item {
+    pub id: String,
+    pub name: String,
+}
+
+/// Everything behind each state's value
+pub type Items = BTreeMap<State, Vec<Item>>;
+
+/// A point in the points file, with the value of every metric sharing the file under its key
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub(crate) struct PointEntry {
+    pub(crate) name: String,
+    pub(crate) state: State,
+    pub(crate) latitude: f64,
+    pub(crate) longitude: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) elevation: Option<f64>,
+    pub(crate) values: Map<String, Value>,
+}
+
+/// A single value per state along with the [`Source`] describing it. There is no way to build one
+/// without a `Source`, so nothing can be written to `generated` without its sources.json entry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Metric {
+    source: Source,
+    values: BTreeMap<State, Option<f64>>,
+    history: Option<History>,
+    months: Option<Months>,
+    points: Option<Vec<Point>>,
+    items: Option<Items>,
+    /// How many things (e.g. weather stations) each state's value comes from
+    samples: Option<BTreeMap<State, usize>>,
+    /// Why states have no value, which isn't written anywhere
+    missing: BTreeMap<State, String>,
+}
+
+impl Metric {
+    /// Values can be plain numbers or `Option`s. A `None` is written as an explicit `null`.
+    pub fn new<V: Into<Option<f64>>>(
+        source: Source,
+        values: impl IntoIterator<Item = (State, V)>,
+    ) -> Self {
+        Self {
+            source,
+            values: values
+                .into_iter()
+                .map(|(state, value)| (state, value.into()))
+                .collect(),
+            history: None,
+            months: None,
+            points: None,
+            items: None,
+            samples: None,
+            missing: BTreeMap::new(),
+        }
+    }
+
+    /// A metric with every year's values, written to its own history file. The values are from
+    /// the latest year which every state with any data has, so they can be compared, and are
+    /// `null` for states without data that year.
+    pub fn with_history(mut source: Source, history: History) -> Self {
+        let year = latest_year(&history);
+        let values = history
+            .iter()
+            .map(|(state, years)| (*state, year.and_then(|year| years.get(&year).copied())))
+            .collect();
+        source.history = Some(format!("{}_history.json", source.key()).into());
+        Self {
+            source,
+            values,
+            history: Some(history),
+            months: None,
+            points: None,
+            items: None,
+            samples: None,
+            missing: BTreeMap::new(),
+        }
+    }
+
+    /// A metric with every month's values, written under [`Source::key`] in the months `file`
+    /// (which other metrics can share). The values are the average month, and are `null` for
+    /// states missing any month.
+    pub fn with_months(
+        mut source: Source,
+        file: impl Into<Cow<'static, str>>,
+        months: Months,
+    ) -> Self {
+        let values = months
+            .iter()
+            .map(|(state, months)| {
+                let total = months.iter().copied().sum::<Option<f64>>();
+                (*state, total.map(|total| total / 12.0))
+            })
+            .collect();
+        source.months = Some(file.into());
+        Self {
+            source,
+            values,
+            history: None,
+            months: Some(months),
+            points: None,
+            items: None,
+            samples: None,
+            missing: BTreeMap::new(),
+        }
+    }
+
+    /// Every place behind the values, written under [`Source::key`] in the points `file` (which
+    /// other metrics can share) so they can be mapped, and each state's value checked
+    pub fn with_points(mut self, file: impl Into<Cow<'static, str>>, points: Vec<Point>) -> Self {
+        self.source.points = Some(file.into());
+        self.points = Some(points);
+        self
+    }
+
+    /// Everything behind each state's value, written under [`Source::key`] in the items `file`
+    /// (which other metrics can share) so the front end can list them
+    pub fn with_items(mut self, file: impl Into<Cow<'static, str>>, items: Items) -> Self {
+        self.source.items = Some(file.into());
+        self.items = Some(items);
+        self
+    }
+
+    /// How many things (e.g. weather stations) each state's value comes from, written to its
+    /// stats file so a value from one station can be told apart from one from a hundred
+    pub fn with_samples(mut self, samples: impl IntoIterator<Item = (State, usize)>) -> Self {
+        self.samples = Some(samples.into_iter().collect());
+        self
+    }
+
+    /// Why each of these states has no value, e.g. its request failed. They're written as an
+    /// explicit `null` and the reasons are only reported, so a partial failure doesn't take every
+    /// other state down with it.
+    pub fn with_missing(mut self, missing: impl IntoIterator<Item = (State, String)>) -> Self {
+        for (state, reason) in missing {
+            self.values.insert(state, None);
+            self.missing.insert(state, reason);
+        }
+        self
+    }
+
+    /// Exactly these values, history, months, points and items, as read back from `generated`
+    pub(crate) fn from_parts(
+        source: Source,
+        values: BTreeMap<State, Option<f64>>,
+        history: Option<History>,
+        months: Option<Months>,
+        points: Option<Vec<Point>>,
+        items: Option<Items>,
+    ) -> Self {
+        Self {
+            source,
+            values,
+            history,
+            months,
+            points,
+            items,
+            samples: None,
+            missing: BTreeMap::new(),
+        }
+    }
+
+    pub fn source(&self) -> &Source {
+        &self.source
+    }
+
+    pub fn values(&self) -> &BTreeMap<State, Option<f64>> {
+        &self.values
+    }
+
+    pub fn get(&self, state: State) -> Option<f64> {
+        self.values.get(&state).copied().flatten()
+    }
+
+    pub fn history(&self) -> Option<&History> {
+        self.history.as_ref()
+    }
+
+    pub fn months(&self) -> Option<&Months> {
+        self.months.as_ref()
+    }
+
+    pub fn points(&self) -> Option<&[Point]> {
+        self.points.as_deref()
+    }
+
+    pub fn items(&self) -> Option<&Items> {
+        self.items.as_ref()
+    }
+
+    pub fn samples(&self) -> Option<&BTreeMap<State, usize>> {
+        self.samples.as_ref()
+    }
+
+    /// Why states have no value, if whatever built this metric said
+    pub fn missing(&self) -> &BTreeMap<State, String> {
+        &self.missing
+    }
+
+    /// Ranks, percentiles, mean and median of [`Metric::values`], in the source's sort order, and
+    /// the samples behind each value if there are any
+    pub fn stats(&self) -> Stats {
+        Stats {
+            samples: self.samples.clone(),
+            ..Stats::new(&self.values, self.source.sort)
+        }
+    }
+
+    /// The year that [`Metric::values`] are from, if this metric has a history
+    pub fn year(&self) -> Option<u16> {
+        latest_year(self.history.as_ref()?)
+    }
+
+    /// The contents of the generated file. Integers are written without a trailing ".0".
+    pub fn to_json(&self) -> Value {
+        self.values
+            .iter()
+            .map(|(state, value)| (state.code().to_owned(), self.value_to_json(*value)))
+            .collect::<serde_json::Map<String, Value>>()
+            .into()
+    }
+
+    /// The contents of the history file, `{state: {year: value}}`, if there is one
+    pub fn history_to_json(&self) -> Option<Value> {
+        let history = self
+            .history
+            .as_ref()?
+            .iter()
+            .map(|(state, years)| {
+                let years = years
+                    .iter()
+                    .map(|(year, value)| (year.to_string(), self.value_to_json(Some(*value))))
+                    .collect::<serde_json::Map<String, Value>>();
+                (state.code().to_owned(), Value::from(years))
+            })
+            .collect::<serde_json::Map<String, Value>>();
+        Some(history.into())
+    }
+
+    /// This metric's part of the months file, `{state: [january, ..., december]}`, if it has one
+    pub fn months_to_json(&self) -> Option<Value> {
+        let months = self
+            .months
+            .as_ref()?
+            .iter()
+            .map(|(state, months)| {
+                let months = months
+                    .iter()
+                    .map(|value| self.value_to_json(*value))
+                    .collect::<Vec<Value>>();
+                (state.code().to_owned(), Value::from(months))
+            })
+            .collect::<serde_json::Map<String, Value>>();
+        Some(months.into())
+    }
+
+    /// This metric's part of the points file, keyed by point id, if it has one
+    pub(crate) fn points_to_json(&self) -> Option<BTreeMap<String, PointEntry>> {
+        let points = self
+            .points
+            .as_ref()?
+            .iter()
+            .map(|point| {
+                let mut values = Map::new();
+                values.insert(
+                    self.source.key().to_owned(),
+                    self.value_to_json(Some(point.value)),
+                );
+                let entry = PointEntry {
+                    name: point.name.clone(),
+                    state: point.state,
+                    latitude: point.latitude,
+                    longitude: point.longitude,
+                    elevation: point.elevation,
+                    values,
+                };
+                (point.id.clone(), entry)
+            })
+            .collect();
+        Some(points)
+    }
+
+    /// This metric's part of the items file, `{state: [{id, name}, ...]}`, if it has one
+    pub fn items_to_json(&self) -> Option<Value> {
+        serde_json::to_value(self.items.as_ref()?).ok()
+    }
+
+    fn value_to_json(&self, value: Option<f64>) -> Value {
+        match (value, self.source.data_type) {
+            (Some(value), DataType::Integer) => Value::from(value.round() as i64),
+            (Some(value), _) => Value::from(value),
+            (None, _) => Value::Null,
+        }
+    }
+}
+
+/// The latest year which every state with any data has
+fn latest_year(history: &History) -> Option<u16> {
+    let mut states = history.values().filter(|years| !years.is_empty());
+    let first = states.next()?;
+    let mut common: BTreeSet<u16> = first.keys().copied().collect();
+    for years in states {
+        common.retain(|year| years.contains_key(year));
+    }
+    common.iter().next_back().copied()
+}
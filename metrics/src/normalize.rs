@@ -0,0 +1,174 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use states::Regions;
+
+use crate::{DataType, History, Metric, Sort, Source};
+
+static LAND_AREA: Source = Source {
+    source: Cow::Borrowed("land_area.json"),
+    name: Cow::Borrowed("Land Area"),
+    data_type: DataType::Number,
+    source_url: Cow::Borrowed(
+        "https://www.census.gov/geographies/reference-files/2010/geo/state-area.html",
+    ),
+    description: Cow::Borrowed("Land area in square miles, not counting water, as of 2010."),
+    units: Some(Cow::Borrowed("sq mi")),
+    min: Some(0.0),
+    sort: Sort::Descending,
+    ..Source::BASE
+};
+
+/// The land area of every state in `regions`, to divide by for [`Per::SquareMile`]
+pub fn land_area(regions: Regions) -> Metric {
+    Metric::new(
+        LAND_AREA.clone(),
+        regions.iter().map(|state| (state, state.land_area())),
+    )
+}
+
+/// What a total is divided by so that big and small states can be compared
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Per {
+    /// Population, from population.json
+    Capita,
+    /// Land area, from [`land_area`]
+    SquareMile,
+}
+
+impl Per {
+    fn suffix(self) -> &'static str {
+        match self {
+            Per::Capita => "per_capita",
+            Per::SquareMile => "per_sq_mi",
+        }
+    }
+
+    fn unit(self) -> &'static str {
+        match self {
+            Per::Capita => "person",
+            Per::SquareMile => "sq mi",
+        }
+    }
+}
+
+/// One entry in normalize.json: a generated metric to divide by population or land area. The
+/// result is written next to it, e.g. "co2_emissions.json" becomes "co2_emissions_per_capita.json".
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Normalize {
+    /// The file in `generated` to divide
+    pub source: Cow<'static, str>,
+    pub per: Per,
+    /// Multiplied into every value after dividing, e.g. 1,000,000 to turn million metric tons per
+    /// person into metric tons per person
+    #[serde(default = "one")]
+    pub scale: f64,
+    /// Units of the result, the original units "per person" (or "per sq mi") by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub units: Option<Cow<'static, str>>,
+}
+
+fn one() -> f64 {
+    1.0
+}
+
+impl Normalize {
+    /// Every entry in a normalize.json file, in order
+    pub fn read_all(path: &Path) -> Result<Vec<Normalize>> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Could not read {}", path.display()))?;
+        serde_json::from_str(&contents).with_context(|| format!("Invalid {}", path.display()))
+    }
+
+    /// The file the result is written to
+    pub fn file(&self) -> String {
+        format!(
+            "{}_{}.json",
+            self.source.trim_end_matches(".json"),
+            self.per.suffix()
+        )
+    }
+
+    /// `metric` divided by `denominator` for every state. When both have a history each year is
+    /// divided by the same year, when only `metric` does every year is divided by the
+    /// denominator's latest values. States without a denominator (or with one of 0) are `null`.
+    pub fn apply(&self, metric: &Metric, denominator: &Metric) -> Result<Metric> {
+        let source = self.source(metric.source())?;
+        let divide = |value: f64, denominator: Option<f64>| match denominator {
+            Some(denominator) if denominator != 0.0 => Some(value / denominator * self.scale),
+            _ => None,
+        };
+        let history = match metric.history() {
+            Some(history) => history,
+            None => {
+                let values = metric.values().iter().map(|(state, value)| {
+                    let value = value.and_then(|value| divide(value, denominator.get(*state)));
+                    (*state, value)
+                });
+                return Ok(Metric::new(source, values));
+            }
+        };
+        let history: History = history
+            .iter()
+            .map(|(state, years)| {
+                let years: BTreeMap<u16, f64> = years
+                    .iter()
+                    .filter_map(|(year, value)| {
+                        let by = match denominator.history() {
+                            Some(history) => history.get(state)?.get(year).copied(),
+                            None => denominator.get(*state),
+                        };
+                        Some((*year, divide(*value, by)?))
+                    })
+                    .collect();
+                (*state, years)
+            })
+            .collect();
+        Ok(Metric::with_history(source, history))
+    }
+
+    fn source(&self, original: &Source) -> Result<Source> {
+        let data_type = match original.data_type {
+            DataType::Percent => {
+                return Err(anyhow!(
+                    "{} is a percent, which can't be divided {}",
+                    original.source,
+                    self.per.suffix().replace('_', " ")
+                ))
+            }
+            DataType::Money => DataType::Money,
+            DataType::Number | DataType::Integer => DataType::Number,
+        };
+        let units = match (&self.units, &original.units) {
+            (Some(units), _) => units.clone(),
+            (None, Some(units)) => format!("{} per {}", units, self.per.unit()).into(),
+            (None, None) => format!("per {}", self.per.unit()).into(),
+        };
+        let (name, by) = match self.per {
+            Per::Capita => ("per Person", "population"),
+            Per::SquareMile => ("per Square Mile", "land area in square miles"),
+        };
+        let description = format!(
+            "{} Divided by each state's {}, in {}.",
+            original.description, by, units
+        );
+        Ok(Source {
+            source: self.file().into(),
+            name: format!("{} {}", original.name, name).into(),
+            data_type,
+            source_url: original.source_url.clone(),
+            description: description.into(),
+            units: Some(units),
+            // Dividing by something positive keeps a value's sign, but nothing else about its range
+            min: original.min.filter(|min| *min >= 0.0).map(|_| 0.0),
+            sort: original.sort,
+            ..Source::BASE
+        })
+    }
+}
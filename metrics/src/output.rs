@@ -0,0 +1,194 @@
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::side_file::{ItemsFile, MonthsFile, PointsFile, SideFile};
+use crate::{Metric, Source};
+
+/// Where metrics get written. Every metric written puts its values in `dir` and its [`Source`] in
+/// the sources file, so the two can't drift apart.
+#[derive(Clone, Debug)]
+pub struct Output {
+    dir: PathBuf,
+    sources: PathBuf,
+}
+
+impl Default for Output {
+    /// `generated` and `sources.json` in the current directory (the root of this repo)
+    fn default() -> Self {
+        Self::new("generated", "sources.json")
+    }
+}
+
+impl Output {
+    pub fn new(dir: impl Into<PathBuf>, sources: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            sources: sources.into(),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn sources_path(&self) -> &Path {
+        &self.sources
+    }
+
+    /// Write each metric's data file, stats file (and history file if it has one), then update its
+    /// entry in the sources file. Existing entries are replaced in place (so the order in
+    /// sources.json is kept) and new ones are appended. Metrics with months, points or items are
+    /// merged into those files, so writing one doesn't drop the others sharing them.
+    pub fn write(&self, metrics: &[Metric]) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        for metric in metrics {
+            let path = self.dir.join(&*metric.source().source);
+            fs::write(&path, serde_json::to_string(&metric.to_json())?)
+                .with_context(|| format!("Could not write {}", path.display()))?;
+            let path = self.dir.join(metric.source().stats_file());
+            fs::write(&path, serde_json::to_string(&metric.stats().to_json())?)
+                .with_context(|| format!("Could not write {}", path.display()))?;
+            if let (Some(file), Some(history)) =
+                (&metric.source().history, metric.history_to_json())
+            {
+                let path = self.dir.join(&**file);
+                fs::write(&path, serde_json::to_string(&history)?)
+                    .with_context(|| format!("Could not write {}", path.display()))?;
+            }
+        }
+        self.write_side::<MonthsFile>(metrics)?;
+        self.write_side::<PointsFile>(metrics)?;
+        self.write_side::<ItemsFile>(metrics)?;
+        let mut sources = self.read_sources()?;
+        for metric in metrics {
+            let new = metric.source();
+            match sources
+                .iter_mut()
+                .find(|source| source.source == new.source)
+            {
+                Some(existing) => *existing = new.clone(),
+                None => sources.push(new.clone()),
+            }
+        }
+        fs::write(&self.sources, serde_json::to_string_pretty(&sources)?)
+            .with_context(|| format!("Could not write {}", self.sources.display()))?;
+        Ok(())
+    }
+
+    /// A metric previously written to `dir`, along with its history if it has one
+    pub fn read(&self, file: &str) -> Result<Metric> {
+        let source = self
+            .read_sources()?
+            .into_iter()
+            .find(|source| source.source == file)
+            .ok_or_else(|| anyhow!("{} is not in {}", file, self.sources.display()))?;
+        let values = self.read_json(file)?;
+        let history = match &source.history {
+            Some(history) if self.dir.join(&**history).exists() => Some(self.read_json(history)?),
+            _ => None,
+        };
+        let months = self.read_side::<MonthsFile>(&source)?;
+        let points = self.read_side::<PointsFile>(&source)?;
+        let items = self.read_side::<ItemsFile>(&source)?;
+        Ok(Metric::from_parts(
+            source, values, history, months, points, items,
+        ))
+    }
+
+    /// Merge every metric's records into the side files of kind `F` they share, read first if
+    /// they were already written. A record in two metrics keeps both values and takes the rest
+    /// from the later one. A metric's value is dropped from records it no longer has, and records
+    /// left without any value are dropped entirely.
+    fn write_side<F: SideFile>(&self, metrics: &[Metric]) -> Result<()> {
+        let mut files: BTreeMap<&str, Map<String, Value>> = BTreeMap::new();
+        for metric in metrics {
+            let (file, records) = match (F::file(metric.source()), F::records(metric)) {
+                (Some(file), Some(records)) => (file, records),
+                _ => continue,
+            };
+            let all = match files.entry(file) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) if self.dir.join(file).exists() => {
+                    entry.insert(self.read_json(file)?)
+                }
+                Entry::Vacant(entry) => entry.insert(Map::new()),
+            };
+            let key = metric.source().key();
+            all.retain(|id, record| {
+                if records.contains_key(id) {
+                    return true;
+                }
+                match F::values(record) {
+                    Some(values) => {
+                        values.remove(key);
+                        !values.is_empty()
+                    }
+                    None => true,
+                }
+            });
+            for (id, mut record) in records {
+                if let Some(mut existing) = all.remove(&id) {
+                    if let (Some(old), Some(new)) =
+                        (F::values(&mut existing), F::values(&mut record))
+                    {
+                        let mut values = std::mem::take(old);
+                        values.extend(std::mem::take(new));
+                        *new = values;
+                    }
+                }
+                all.insert(id, record);
+            }
+        }
+        for (file, all) in files {
+            let path = self.dir.join(file);
+            fs::write(&path, serde_json::to_string(&all)?)
+                .with_context(|| format!("Could not write {}", path.display()))?;
+        }
+        Ok(())
+    }
+
+    /// `source`'s part of its side file of kind `F`, if it has one and it's been written
+    fn read_side<F: SideFile>(&self, source: &Source) -> Result<Option<F::Part>> {
+        let file = match F::file(source) {
+            Some(file) if self.dir.join(file).exists() => file,
+            _ => return Ok(None),
+        };
+        let records: Map<String, Value> = self.read_json(file)?;
+        let path = self.dir.join(file);
+        records
+            .into_iter()
+            .filter_map(|(id, mut record)| {
+                let value = F::values(&mut record)?.remove(source.key())?;
+                Some(
+                    F::entry(&id, &record, value)
+                        .with_context(|| format!("Invalid {} in {}", id, path.display())),
+                )
+            })
+            .collect::<Result<_>>()
+            .map(Some)
+    }
+
+    fn read_json<T: DeserializeOwned>(&self, file: &str) -> Result<T> {
+        let path = self.dir.join(file);
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("Could not read {}", path.display()))?;
+        serde_json::from_str(&contents).with_context(|| format!("Invalid {}", path.display()))
+    }
+
+    /// Every entry currently in the sources file, or nothing if it doesn't exist yet.
+    pub fn read_sources(&self) -> Result<Vec<Source>> {
+        if !self.sources.exists() {
+            return Ok(Vec::new());
+        }
+        let contents = fs::read_to_string(&self.sources)
+            .with_context(|| format!("Could not read {}", self.sources.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid sources file {}", self.sources.display()))
+    }
+}
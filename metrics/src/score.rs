@@ -0,0 +1,239 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use states::{Regions, State};
+
+use crate::stats::rank;
+use crate::{DataType, Metric, Sort, Source};
+
+/// How each metric's values are put on the same scale before they're weighted
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scaling {
+    /// Standard deviations from the mean of every state
+    ZScore,
+    /// 0 for the worst state up to 1 for the best
+    MinMax,
+}
+
+/// One metric in a [`Profile`] and how much it counts
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Weight {
+    /// The file in `generated`, e.g. "poverty_rate.json"
+    pub metric: String,
+    pub weight: f64,
+    /// For metrics like poverty_rate or cost_index where a smaller value is better. Defaults to
+    /// whether the metric's sort is [`Sort::Ascending`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lower_is_better: Option<bool>,
+}
+
+/// A set of weights to score states by, read from a file in `profiles`. The scores are written to
+/// "score_<id>.json" and the ranks (1 is the best) to "score_<id>_rank.json".
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Profile {
+    /// The name of the file, without ".json"
+    #[serde(skip)]
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub scaling: Scaling,
+    pub weights: Vec<Weight>,
+}
+
+impl Profile {
+    pub fn read(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Could not read {}", path.display()))?;
+        let mut profile: Profile = serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid profile {}", path.display()))?;
+        profile.id = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(|| anyhow!("Invalid profile name {}", path.display()))?
+            .to_owned();
+        Ok(profile)
+    }
+
+    /// Every profile in `dir`, by id
+    pub fn read_all(dir: &Path) -> Result<Vec<Self>> {
+        let mut paths = Vec::new();
+        for entry in
+            fs::read_dir(dir).with_context(|| format!("Could not read {}", dir.display()))?
+        {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "json")
+            {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        paths.iter().map(|path| Self::read(path)).collect()
+    }
+
+    /// Every metric the profile needs
+    pub fn files(&self) -> impl Iterator<Item = &str> {
+        self.weights.iter().map(|weight| weight.metric.as_str())
+    }
+
+    /// The score and rank of every state in `regions`. `inputs` are the metrics named in the
+    /// weights, by file. A state's score is the weighted average of its scaled values, over only
+    /// the metrics it has a value for, so a missing value neither helps nor hurts it. States with
+    /// no values at all get `null`.
+    pub fn score(&self, inputs: &HashMap<String, Metric>, regions: Regions) -> Result<[Metric; 2]> {
+        let mut totals: BTreeMap<State, (f64, f64)> = BTreeMap::new();
+        for weight in &self.weights {
+            let metric = inputs
+                .get(&weight.metric)
+                .ok_or_else(|| anyhow!("Profile {} needs {}", self.id, weight.metric))?;
+            let values: Vec<(State, f64)> = regions
+                .iter()
+                .filter_map(|state| Some((state, metric.get(state)?)))
+                .collect();
+            let lower_is_better = weight
+                .lower_is_better
+                .unwrap_or(metric.source().sort == Sort::Ascending);
+            for (state, scaled) in self.scale(&values, lower_is_better) {
+                let total = totals.entry(state).or_insert((0.0, 0.0));
+                total.0 += scaled * weight.weight;
+                total.1 += weight.weight;
+            }
+        }
+        let scores: BTreeMap<State, Option<f64>> = regions
+            .iter()
+            .map(|state| {
+                let score = totals
+                    .get(&state)
+                    .filter(|(_, weights)| *weights != 0.0)
+                    .map(|(total, weights)| total / weights);
+                (state, score)
+            })
+            .collect();
+        let ranks = rank(&scores, Sort::Descending)
+            .into_iter()
+            .map(|(state, rank)| (state, rank.map(|rank| rank as f64)));
+        Ok([
+            Metric::new(self.source(), scores),
+            Metric::new(self.rank_source(), ranks),
+        ])
+    }
+
+    /// Each value on this profile's [`Scaling`], flipped so that higher is always better
+    fn scale(&self, values: &[(State, f64)], lower_is_better: bool) -> Vec<(State, f64)> {
+        let count = values.len() as f64;
+        let sign = if lower_is_better { -1.0 } else { 1.0 };
+        match self.scaling {
+            Scaling::ZScore => {
+                let mean = values.iter().map(|(_, value)| value).sum::<f64>() / count;
+                let variance = values
+                    .iter()
+                    .map(|(_, value)| (value - mean).powi(2))
+                    .sum::<f64>()
+                    / count;
+                let deviation = variance.sqrt();
+                values
+                    .iter()
+                    .map(|(state, value)| {
+                        // Every state is the same, so none is better than average
+                        let z = if deviation > 0.0 {
+                            (value - mean) / deviation
+                        } else {
+                            0.0
+                        };
+                        (*state, z * sign)
+                    })
+                    .collect()
+            }
+            Scaling::MinMax => {
+                let min = values
+                    .iter()
+                    .map(|(_, value)| *value)
+                    .fold(f64::INFINITY, f64::min);
+                let max = values
+                    .iter()
+                    .map(|(_, value)| *value)
+                    .fold(f64::NEG_INFINITY, f64::max);
+                values
+                    .iter()
+                    .map(|(state, value)| {
+                        let scaled = if max > min {
+                            (value - min) / (max - min)
+                        } else {
+                            0.5
+                        };
+                        let scaled = if lower_is_better {
+                            1.0 - scaled
+                        } else {
+                            scaled
+                        };
+                        (*state, scaled)
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    fn source(&self) -> Source {
+        let (data_type, method, min, max) = match self.scaling {
+            Scaling::ZScore => (
+                DataType::Number,
+                "the average number of standard deviations better than the mean",
+                None,
+                None,
+            ),
+            Scaling::MinMax => (
+                DataType::Percent,
+                "the average of how close each metric is to the best state, from 0% for the worst",
+                Some(0.0),
+                Some(1.0),
+            ),
+        };
+        let metrics: Vec<&str> = self
+            .files()
+            .map(|file| file.trim_end_matches(".json"))
+            .collect();
+        Source {
+            source: format!("score_{}.json", self.id).into(),
+            name: format!("{} Score", self.name).into(),
+            data_type,
+            source_url: format!("{}/profiles/{}.json", REPO_URL, self.id).into(),
+            description: format!(
+                "{} Scored as {}, weighted across {}.",
+                self.description,
+                method,
+                metrics.join(", ")
+            )
+            .into(),
+            min,
+            max,
+            sort: Sort::Descending,
+            ..Source::BASE
+        }
+    }
+
+    fn rank_source(&self) -> Source {
+        Source {
+            source: format!("score_{}_rank.json", self.id).into(),
+            name: format!("{} Rank", self.name).into(),
+            data_type: DataType::Integer,
+            source_url: format!("{}/profiles/{}.json", REPO_URL, self.id).into(),
+            description: format!(
+                "The rank of the state by {} Score. 1 is the best state, ties share a rank.",
+                self.name
+            )
+            .into(),
+            min: Some(1.0),
+            sort: Sort::Ascending,
+            ..Source::BASE
+        }
+    }
+}
+
+/// Where profiles can be read, for attribution of the scores
+const REPO_URL: &str = "https://github.com/dbanty/whatstatedata/blob/main";
@@ -0,0 +1,238 @@
+use std::iter::FromIterator;
+
+use anyhow::{anyhow, Result};
+use serde_json::{Map, Value};
+
+use states::{Regions, State};
+
+use crate::metric::PointEntry;
+use crate::validate::check_value;
+use crate::{Item, Items, Metric, Months, Point, Source};
+
+/// A file which several metrics share, each with its part under [`Source::key`], like a months,
+/// points or items file. Every one is an object of records (keyed by state code or point id) with
+/// each metric's value in the record's values, so [`crate::Output`] writes, merges and reads them
+/// and [`crate::validate_file`] checks them the same way.
+pub(crate) trait SideFile {
+    /// Everything a metric has in the file, e.g. [`Months`]
+    type Part: FromIterator<Self::Entry>;
+    /// A metric's part of one record
+    type Entry;
+
+    /// The source's field naming the file
+    fn file(source: &Source) -> Option<&str>;
+
+    /// The metric's records, each with only its own value, if it has any
+    fn records(metric: &Metric) -> Option<Map<String, Value>>;
+
+    /// Every metric's value in `record`, under its key
+    fn values(record: &mut Value) -> Option<&mut Map<String, Value>>;
+
+    /// A metric's entry from its `value` in `record`, which is keyed by `id`
+    fn entry(id: &str, record: &Value, value: Value) -> Result<Self::Entry>;
+
+    /// What's wrong with the record keyed by `id`, ignoring its values
+    fn check_record(id: &str, record: &Value, regions: Regions) -> Vec<String>;
+
+    /// What's wrong with `source`'s value in the record keyed by `id`
+    fn check_part(source: &Source, id: &str, value: &Value) -> Vec<String>;
+}
+
+/// `{state: {key: [january, ..., december]}}`, see [`Metric::with_months`]
+pub(crate) struct MonthsFile;
+
+impl SideFile for MonthsFile {
+    type Part = Months;
+    type Entry = (State, [Option<f64>; 12]);
+
+    fn file(source: &Source) -> Option<&str> {
+        source.months.as_deref()
+    }
+
+    fn records(metric: &Metric) -> Option<Map<String, Value>> {
+        by_state(metric, metric.months_to_json()?)
+    }
+
+    fn values(record: &mut Value) -> Option<&mut Map<String, Value>> {
+        record.as_object_mut()
+    }
+
+    fn entry(id: &str, _: &Value, value: Value) -> Result<Self::Entry> {
+        Ok((state(id)?, serde_json::from_value(value)?))
+    }
+
+    fn check_record(id: &str, _: &Value, regions: Regions) -> Vec<String> {
+        check_state(id, regions)
+    }
+
+    fn check_part(source: &Source, id: &str, value: &Value) -> Vec<String> {
+        let months = match value {
+            Value::Array(months) if months.len() == 12 => months,
+            _ => {
+                return vec![format!(
+                    "{} {} is not an array of 12 months",
+                    id,
+                    source.key()
+                )]
+            }
+        };
+        months
+            .iter()
+            .enumerate()
+            .filter_map(|(month, value)| {
+                let message = check_value(source, value)?;
+                Some(format!(
+                    "{} {} month {} {}",
+                    id,
+                    source.key(),
+                    month + 1,
+                    message
+                ))
+            })
+            .collect()
+    }
+}
+
+/// `{id: {name, state, latitude, longitude, elevation, values: {key: value}}}`, see
+/// [`Metric::with_points`]. A point's name and location come from whichever metric wrote it last.
+pub(crate) struct PointsFile;
+
+impl SideFile for PointsFile {
+    type Part = Vec<Point>;
+    type Entry = Point;
+
+    fn file(source: &Source) -> Option<&str> {
+        source.points.as_deref()
+    }
+
+    fn records(metric: &Metric) -> Option<Map<String, Value>> {
+        metric
+            .points_to_json()?
+            .into_iter()
+            .map(|(id, entry)| Some((id, serde_json::to_value(entry).ok()?)))
+            .collect()
+    }
+
+    fn values(record: &mut Value) -> Option<&mut Map<String, Value>> {
+        record.get_mut("values")?.as_object_mut()
+    }
+
+    fn entry(id: &str, record: &Value, value: Value) -> Result<Self::Entry> {
+        let entry: PointEntry = serde_json::from_value(record.clone())?;
+        Ok(Point {
+            id: id.to_owned(),
+            name: entry.name,
+            state: entry.state,
+            latitude: entry.latitude,
+            longitude: entry.longitude,
+            elevation: entry.elevation,
+            value: value
+                .as_f64()
+                .ok_or_else(|| anyhow!("{} is not a number", value))?,
+        })
+    }
+
+    fn check_record(id: &str, record: &Value, regions: Regions) -> Vec<String> {
+        let point: PointEntry = match serde_json::from_value(record.clone()) {
+            Ok(point) => point,
+            Err(e) => return vec![format!("{} is not a point: {}", id, e)],
+        };
+        let mut problems = Vec::new();
+        if !regions.contains(point.state) {
+            problems.push(format!("{} is in unexpected {}", id, point.state));
+        }
+        if !(-90.0..=90.0).contains(&point.latitude) || !(-180.0..=180.0).contains(&point.longitude)
+        {
+            problems.push(format!(
+                "{} is at {}, {}, which is not a latitude and longitude",
+                id, point.latitude, point.longitude
+            ));
+        }
+        problems
+    }
+
+    fn check_part(source: &Source, id: &str, value: &Value) -> Vec<String> {
+        check_value(source, value)
+            .map(|message| format!("{} {} {}", id, source.key(), message))
+            .into_iter()
+            .collect()
+    }
+}
+
+/// `{state: {key: [{id, name}, ...]}}`, see [`Metric::with_items`]
+pub(crate) struct ItemsFile;
+
+impl SideFile for ItemsFile {
+    type Part = Items;
+    type Entry = (State, Vec<Item>);
+
+    fn file(source: &Source) -> Option<&str> {
+        source.items.as_deref()
+    }
+
+    fn records(metric: &Metric) -> Option<Map<String, Value>> {
+        by_state(metric, metric.items_to_json()?)
+    }
+
+    fn values(record: &mut Value) -> Option<&mut Map<String, Value>> {
+        record.as_object_mut()
+    }
+
+    fn entry(id: &str, _: &Value, value: Value) -> Result<Self::Entry> {
+        Ok((state(id)?, serde_json::from_value(value)?))
+    }
+
+    fn check_record(id: &str, _: &Value, regions: Regions) -> Vec<String> {
+        check_state(id, regions)
+    }
+
+    fn check_part(source: &Source, id: &str, value: &Value) -> Vec<String> {
+        let items = match value {
+            Value::Array(items) => items,
+            _ => return vec![format!("{} {} is not a list of items", id, source.key())],
+        };
+        items
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| {
+                let e = serde_json::from_value::<Item>(item.clone()).err()?;
+                Some(format!(
+                    "{} {} item {} is not an item: {}",
+                    id,
+                    source.key(),
+                    index + 1,
+                    e
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Records keyed by state, `{state: {key: part}}`, from the metric's `{state: part}`
+fn by_state(metric: &Metric, parts: Value) -> Option<Map<String, Value>> {
+    let parts = match parts {
+        Value::Object(parts) => parts,
+        _ => return None,
+    };
+    let key = metric.source().key();
+    let records = parts
+        .into_iter()
+        .map(|(state, part)| {
+            let mut values = Map::new();
+            values.insert(key.to_owned(), part);
+            (state, Value::Object(values))
+        })
+        .collect();
+    Some(records)
+}
+
+fn state(code: &str) -> Result<State> {
+    State::from_code(code).ok_or_else(|| anyhow!("{} is not a state code", code))
+}
+
+fn check_state(code: &str, regions: Regions) -> Vec<String> {
+    match State::from_code(code) {
+        Some(state) if regions.contains(state) => Vec::new(),
+        _ => vec![format!("unexpected key {}", code)],
+    }
+}
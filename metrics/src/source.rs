@@ -0,0 +1,103 @@
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+
+/// How the front end should display a metric's values.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DataType {
+    /// A fraction, where 1.0 is 100%
+    Percent,
+    /// Dollars
+    Money,
+    Number,
+    Integer,
+}
+
+/// Which way states are ranked in a metric's stats file
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Sort {
+    /// The highest value is ranked 1st, e.g. median_household_income
+    Descending,
+    /// The lowest value is ranked 1st, e.g. poverty_rate or cost_index
+    Ascending,
+}
+
+/// Everything about a metric except its values. This is exactly one entry in sources.json.
+///
+/// Fields are `Cow` so that collectors can declare these as `static`s while derived metrics can
+/// build them at runtime.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Source {
+    /// The name of the file in `generated`, e.g. "co2_emissions.json"
+    pub source: Cow<'static, str>,
+    /// Human readable name to display
+    pub name: Cow<'static, str>,
+    pub data_type: DataType,
+    /// Where the data came from, for attribution
+    pub source_url: Cow<'static, str>,
+    pub description: Cow<'static, str>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub units: Option<Cow<'static, str>>,
+    /// Smallest plausible value, checked by [`crate::validate`] on top of the `data_type` checks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    /// Largest plausible value, checked by [`crate::validate`] on top of the `data_type` checks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+    /// Which way states are ranked in the stats file, see [`Source::stats_file`]
+    pub sort: Sort,
+    /// The file in `generated` with every year's values, e.g. "co2_emissions_history.json". Set
+    /// by [`crate::Metric::with_history`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history: Option<Cow<'static, str>>,
+    /// The file in `generated` with each state's value for every month, e.g.
+    /// "climate_profile.json", which can be shared by several metrics. Set by
+    /// [`crate::Metric::with_months`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub months: Option<Cow<'static, str>>,
+    /// The file in `generated` with every place behind the values, e.g. "noaa_stations.json",
+    /// which can be shared by several metrics. Set by [`crate::Metric::with_points`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub points: Option<Cow<'static, str>>,
+    /// The file in `generated` listing what's behind each state's value, e.g. "nps_parks.json",
+    /// which can be shared by several metrics. Set by [`crate::Metric::with_items`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub items: Option<Cow<'static, str>>,
+}
+
+impl Source {
+    /// Every optional field unset, so a `Source` only spells out what it uses and fills in the
+    /// rest with `..Source::BASE`. The required fields are empty and must always be set.
+    ///
+    /// This is a `const` rather than a `const fn` because a `static` can only drop the fields
+    /// replaced in a struct update when it can see they're empty.
+    pub const BASE: Source = Source {
+        source: Cow::Borrowed(""),
+        name: Cow::Borrowed(""),
+        data_type: DataType::Number,
+        source_url: Cow::Borrowed(""),
+        description: Cow::Borrowed(""),
+        units: None,
+        min: None,
+        max: None,
+        sort: Sort::Descending,
+        history: None,
+        months: None,
+        points: None,
+        items: None,
+    };
+
+    /// The file in `generated` with each state's rank and percentile and the national mean and
+    /// median, e.g. "co2_emissions_stats.json". Every metric has one, see [`crate::Stats`].
+    pub fn stats_file(&self) -> String {
+        format!("{}_stats.json", self.key())
+    }
+
+    /// The file without ".json", which is also the metric's key in its months, points and items
+    /// files
+    pub fn key(&self) -> &str {
+        self.source.trim_end_matches(".json")
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use states::State;
+
+use crate::Sort;
+
+/// Where each state stands among every state with a value, and the national mean and median, so
+/// the front end doesn't have to work them out. Written to [`crate::Source::stats_file`], along
+/// with how many samples each value comes from if the metric has them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stats {
+    /// How many states have a value
+    pub count: usize,
+    /// The average of every state's value, not weighted by population
+    pub mean: Option<f64>,
+    pub median: Option<f64>,
+    /// 1 for the first state in the metric's [`Sort`] order, see [`rank`]
+    pub rank: BTreeMap<State, Option<usize>>,
+    /// The share of the other states that each state ties or beats, from 0 for the last state to
+    /// 1 for the first
+    pub percentile: BTreeMap<State, Option<f64>>,
+    /// How many things (e.g. weather stations) each state's value comes from, see
+    /// [`crate::Metric::with_samples`]
+    pub samples: Option<BTreeMap<State, usize>>,
+}
+
+impl Stats {
+    /// Stats for `values`, where states without a value get no rank or percentile
+    pub fn new(values: &BTreeMap<State, Option<f64>>, sort: Sort) -> Self {
+        let mut sorted: Vec<f64> = values.values().flatten().copied().collect();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let count = sorted.len();
+        let mean = if count == 0 {
+            None
+        } else {
+            Some(sorted.iter().sum::<f64>() / count as f64)
+        };
+        let median = match count {
+            0 => None,
+            _ if count % 2 == 0 => Some((sorted[count / 2 - 1] + sorted[count / 2]) / 2.0),
+            _ => Some(sorted[count / 2]),
+        };
+        let rank = rank(values, sort);
+        let percentile = rank
+            .iter()
+            .map(|(state, rank)| {
+                let percentile = rank.map(|rank| {
+                    if count > 1 {
+                        (count - rank) as f64 / (count - 1) as f64
+                    } else {
+                        1.0
+                    }
+                });
+                (*state, percentile)
+            })
+            .collect();
+        Self {
+            count,
+            mean,
+            median,
+            rank,
+            percentile,
+            samples: None,
+        }
+    }
+
+    /// The contents of the stats file
+    pub fn to_json(&self) -> Value {
+        let mut stats = json!({
+            "count": self.count,
+            "mean": self.mean,
+            "median": self.median,
+            "rank": by_state(&self.rank),
+            "percentile": by_state(&self.percentile),
+        });
+        if let Some(samples) = &self.samples {
+            stats["samples"] = by_state(samples);
+        }
+        stats
+    }
+}
+
+/// `{state: value}` for every state in `values`
+fn by_state<T: Serialize>(values: &BTreeMap<State, T>) -> Value {
+    values
+        .iter()
+        .map(|(state, value)| (state.code().to_owned(), json!(value)))
+        .collect::<serde_json::Map<String, Value>>()
+        .into()
+}
+
+/// Each state's place in `sort` order. States with equal values share a rank and the next rank is
+/// skipped (1, 2, 2, 4). States without a value have no rank.
+pub(crate) fn rank(
+    values: &BTreeMap<State, Option<f64>>,
+    sort: Sort,
+) -> BTreeMap<State, Option<usize>> {
+    values
+        .iter()
+        .map(|(state, value)| {
+            let rank = value.map(|value| {
+                let ahead = values
+                    .values()
+                    .flatten()
+                    .filter(|other| match sort {
+                        Sort::Descending => **other > value,
+                        Sort::Ascending => **other < value,
+                    })
+                    .count();
+                ahead + 1
+            });
+            (*state, rank)
+        })
+        .collect()
+}
@@ -0,0 +1,269 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+
+use serde_json::Value;
+
+use states::{Regions, State};
+
+use crate::side_file::{ItemsFile, MonthsFile, PointsFile, SideFile};
+use crate::{DataType, Output, Source};
+
+/// Something wrong with one generated file
+#[derive(Clone, Debug, PartialEq)]
+pub struct Problem {
+    /// The file in `generated`, e.g. "cost_rank.json"
+    pub file: String,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.file, self.message)
+    }
+}
+
+/// Check every file listed in the sources file. See [`validate_file`] for what is checked.
+pub fn validate(output: &Output, regions: Regions) -> anyhow::Result<Vec<Problem>> {
+    Ok(output
+        .read_sources()?
+        .iter()
+        .flat_map(|source| validate_file(output, source, regions))
+        .collect())
+}
+
+/// Check that the generated file for `source` exists, has a value (or an explicit null) for every
+/// state in `regions` and nothing else, and that every value matches the declared `data_type` and
+/// lies within a plausible range. Every year in the history file, if there is one, is checked the
+/// same way, as is every month in the months file and every point in the points file, every item
+/// in the items file must have an id and name, and the stats file must rank every state in
+/// `regions`.
+pub fn validate_file(output: &Output, source: &Source, regions: Regions) -> Vec<Problem> {
+    let mut problems = validate_latest(output, source, regions);
+    problems.extend(validate_stats(output, source, regions));
+    if let Some(history) = &source.history {
+        problems.extend(validate_history(output, source, history, regions));
+    }
+    problems.extend(validate_side::<MonthsFile>(output, source, regions));
+    problems.extend(validate_side::<PointsFile>(output, source, regions));
+    problems.extend(validate_side::<ItemsFile>(output, source, regions));
+    problems
+}
+
+fn validate_latest(output: &Output, source: &Source, regions: Regions) -> Vec<Problem> {
+    let problem = |message: String| Problem {
+        file: source.source.to_string(),
+        message,
+    };
+    let values = match read_states(output, &source.source) {
+        Ok(values) => values,
+        Err(message) => return vec![problem(message)],
+    };
+
+    let mut problems = Vec::new();
+    let missing: Vec<&str> = regions
+        .iter()
+        .map(State::code)
+        .filter(|code| !values.contains_key(*code))
+        .collect();
+    if !missing.is_empty() {
+        problems.push(problem(format!("missing {}", missing.join(", "))));
+    }
+    for (key, value) in &values {
+        let state = match State::from_code(key) {
+            Some(state) if regions.contains(state) => state,
+            _ => {
+                problems.push(problem(format!("unexpected key {}", key)));
+                continue;
+            }
+        };
+        if let Some(message) = check_value(source, value) {
+            problems.push(problem(format!("{} {}", state, message)));
+        }
+    }
+    problems
+}
+
+/// Like [`validate_latest`] except states may be missing, since not every state has history
+fn validate_history(
+    output: &Output,
+    source: &Source,
+    file: &str,
+    regions: Regions,
+) -> Vec<Problem> {
+    let problem = |message: String| Problem {
+        file: file.to_owned(),
+        message,
+    };
+    let states = match read_states(output, file) {
+        Ok(states) => states,
+        Err(message) => return vec![problem(message)],
+    };
+
+    let mut problems = Vec::new();
+    for (key, years) in &states {
+        let state = match State::from_code(key) {
+            Some(state) if regions.contains(state) => state,
+            _ => {
+                problems.push(problem(format!("unexpected key {}", key)));
+                continue;
+            }
+        };
+        let years = match years.as_object() {
+            Some(years) => years,
+            None => {
+                problems.push(problem(format!("{} is not an object of years", state)));
+                continue;
+            }
+        };
+        for (year, value) in years {
+            if year.parse::<u16>().is_err() {
+                problems.push(problem(format!("{} has an invalid year {}", state, year)));
+            } else if let Some(message) = check_value(source, value) {
+                problems.push(problem(format!("{} {} {}", state, year, message)));
+            }
+        }
+    }
+    problems
+}
+
+/// Every record in `source`'s side file of kind `F`, and its value under the source's key if it
+/// has one. A record with something wrong with it isn't checked any further.
+fn validate_side<F: SideFile>(output: &Output, source: &Source, regions: Regions) -> Vec<Problem> {
+    let file = match F::file(source) {
+        Some(file) => file,
+        None => return Vec::new(),
+    };
+    let problem = |message: String| Problem {
+        file: file.to_owned(),
+        message,
+    };
+    let records = match read_states(output, file) {
+        Ok(records) => records,
+        Err(message) => return vec![problem(message)],
+    };
+
+    let mut problems = Vec::new();
+    for (id, mut record) in records {
+        let mut messages = F::check_record(&id, &record, regions);
+        if messages.is_empty() {
+            match F::values(&mut record) {
+                Some(values) => {
+                    if let Some(value) = values.get(source.key()) {
+                        messages.extend(F::check_part(source, &id, value));
+                    }
+                }
+                None => messages.push(format!("{} is not an object", id)),
+            }
+        }
+        problems.extend(messages.into_iter().map(problem));
+    }
+    problems
+}
+
+/// Every state in `regions` has a rank from 1 to the count and a percentile from 0 to 1, or
+/// `null`s, and any samples are whole numbers for states in `regions`
+fn validate_stats(output: &Output, source: &Source, regions: Regions) -> Vec<Problem> {
+    let file = source.stats_file();
+    let problem = |message: String| Problem {
+        file: file.clone(),
+        message,
+    };
+    let stats = match read_states(output, &file) {
+        Ok(stats) => stats,
+        Err(message) => return vec![problem(message)],
+    };
+    let count = match stats.get("count").and_then(Value::as_u64) {
+        Some(count) => count as f64,
+        None => return vec![problem("missing count".to_owned())],
+    };
+
+    let mut problems = Vec::new();
+    for (key, min, max) in [("rank", 1.0, count), ("percentile", 0.0, 1.0)] {
+        let values = match stats.get(key).and_then(Value::as_object) {
+            Some(values) => values,
+            None => {
+                problems.push(problem(format!("missing {}", key)));
+                continue;
+            }
+        };
+        let missing: Vec<&str> = regions
+            .iter()
+            .map(State::code)
+            .filter(|code| !values.contains_key(*code))
+            .collect();
+        if !missing.is_empty() {
+            problems.push(problem(format!("{} missing {}", key, missing.join(", "))));
+        }
+        for state in regions.iter() {
+            match values.get(state.code()) {
+                None | Some(Value::Null) => {}
+                Some(value) => match value.as_f64() {
+                    Some(value) if value >= min && value <= max => {}
+                    _ => problems.push(problem(format!(
+                        "{} {} is {}, expected {} to {}",
+                        state, key, value, min, max
+                    ))),
+                },
+            }
+        }
+    }
+    match stats.get("samples") {
+        None => {}
+        Some(Value::Object(samples)) => {
+            for (code, value) in samples {
+                if !State::from_code(code).is_some_and(|state| regions.contains(state)) {
+                    problems.push(problem(format!("samples for {}, not in REGIONS", code)));
+                } else if value.as_u64().is_none() {
+                    problems.push(problem(format!(
+                        "{} samples is {}, expected a whole number",
+                        code, value
+                    )));
+                }
+            }
+        }
+        Some(samples) => problems.push(problem(format!(
+            "samples is {}, expected an object of state codes",
+            samples
+        ))),
+    }
+    problems
+}
+
+/// A generated file as an object keyed by state code (or point id), or why it couldn't be read
+fn read_states(output: &Output, file: &str) -> Result<BTreeMap<String, Value>, String> {
+    let path = output.dir().join(file);
+    let contents = fs::read_to_string(&path)
+        .map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+    serde_json::from_str(&contents).map_err(|e| format!("not an object of state codes: {}", e))
+}
+
+/// Why `value` isn't valid for `source`, if it isn't
+pub(crate) fn check_value(source: &Source, value: &Value) -> Option<String> {
+    let number = match value {
+        Value::Null => return None,
+        Value::Number(number) => number,
+        _ => return Some(format!("is {}, expected a number or null", value)),
+    };
+    if source.data_type == DataType::Integer && !(number.is_i64() || number.is_u64()) {
+        return Some(format!("is {}, expected an integer", number));
+    }
+    let number = number.as_f64()?;
+    let (default_min, default_max) = match source.data_type {
+        // Percents are fractions, a change can be negative but never more than 100%
+        DataType::Percent => (Some(-1.0), Some(1.0)),
+        DataType::Money => (Some(0.0), None),
+        DataType::Number | DataType::Integer => (None, None),
+    };
+    if let Some(min) = source.min.or(default_min) {
+        if number < min {
+            return Some(format!("is {}, below the minimum of {}", number, min));
+        }
+    }
+    if let Some(max) = source.max.or(default_max) {
+        if number > max {
+            return Some(format!("is {}, above the maximum of {}", number, max));
+        }
+    }
+    None
+}
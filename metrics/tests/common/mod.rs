@@ -0,0 +1,52 @@
+//! Shared by the metrics tests, which write to a `generated` of their own
+
+// Each test file uses only some of these
+#![allow(dead_code)]
+
+use std::borrow::Cow;
+use std::fs;
+use std::path::Path;
+
+use serde_json::Value;
+
+use metrics::{validate, DataType, Output, Sort, Source};
+use states::Regions;
+
+/// A valid source for `file` (whose name is also its key in side files)
+pub fn source(file: &'static str, data_type: DataType) -> Source {
+    Source {
+        source: Cow::Borrowed(file),
+        name: Cow::Borrowed("Test"),
+        data_type,
+        source_url: Cow::Borrowed("https://example.com"),
+        description: Cow::Borrowed("Something about each state."),
+        sort: Sort::Descending,
+        ..Source::BASE
+    }
+}
+
+/// An empty `generated` and sources file for the test called `name`
+pub fn output(name: &str) -> Output {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = fs::remove_dir_all(&dir);
+    Output::new(dir.join("generated"), dir.join("sources.json"))
+}
+
+/// The contents of `file` in `output`'s `generated`
+pub fn read(output: &Output, file: &str) -> Value {
+    serde_json::from_str(&fs::read_to_string(output.dir().join(file)).unwrap()).unwrap()
+}
+
+/// Replace `file` in `output`'s `generated`
+pub fn overwrite(output: &Output, file: &str, contents: &str) {
+    fs::write(output.dir().join(file), contents).unwrap();
+}
+
+/// Everything wrong with `output` for every state, as it's reported
+pub fn problems(output: &Output) -> Vec<String> {
+    validate(output, Regions::States)
+        .unwrap()
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
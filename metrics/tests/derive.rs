@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use metrics::{DataType, Derived, Expression, Metric, Source};
+use states::{Regions, State};
+
+mod common;
+
+fn source(file: &'static str) -> Source {
+    common::source(file, DataType::Number)
+}
+
+fn derived(expression: &str) -> Derived {
+    Derived {
+        source: source("derived.json"),
+        expression: expression.to_owned(),
+    }
+}
+
+fn evaluate(expression: &str) -> Option<f64> {
+    Expression::parse(expression)
+        .unwrap()
+        .evaluate(&|file| match file {
+            "two.json" => Some(2.0),
+            "missing.json" => None,
+            _ => panic!("unexpected {}", file),
+        })
+}
+
+#[test]
+fn arithmetic_follows_precedence() {
+    assert_eq!(evaluate("1 + 2 * 3"), Some(7.0));
+    assert_eq!(evaluate("(1 + 2) * 3"), Some(9.0));
+    assert_eq!(evaluate("10 - 4 - 3"), Some(3.0));
+    assert_eq!(evaluate("12 / 2 / 3"), Some(2.0));
+    assert_eq!(evaluate("-two * -(1 - 4)"), Some(-6.0));
+    assert_eq!(evaluate("1_000 * two / .5"), Some(4000.0));
+}
+
+#[test]
+fn missing_values_propagate() {
+    assert_eq!(evaluate("two + missing * 0"), None);
+    assert_eq!(evaluate("-missing"), None);
+    assert_eq!(evaluate("two / (two - 2)"), None);
+}
+
+#[test]
+fn files_are_listed_once() {
+    let expression = Expression::parse("(summer_temp - winter_temp) / summer_temp").unwrap();
+    assert_eq!(expression.files(), ["summer_temp.json", "winter_temp.json"]);
+}
+
+#[test]
+fn invalid_expressions_are_rejected() {
+    let error = |expression: &str| Expression::parse(expression).unwrap_err().to_string();
+    assert_eq!(error("summer_temp -"), "unexpected end");
+    assert_eq!(error("(1 + 2"), "expected \")\"");
+    assert_eq!(error("1 2"), "unexpected 2");
+    assert_eq!(error("Summer"), "unexpected \"S\"");
+    assert_eq!(error("1.2.3"), "invalid number 1.2.3");
+    assert_eq!(
+        format!("{:#}", derived("a +").expression().unwrap_err()),
+        "Invalid expression for derived.json: unexpected end"
+    );
+}
+
+#[test]
+fn every_region_is_evaluated() {
+    let summer = Metric::new(
+        source("summer_temp.json"),
+        vec![
+            (State::Maryland, Some(75.0)),
+            (State::Alaska, None),
+            (State::DistrictOfColumbia, Some(78.0)),
+        ],
+    );
+    let winter = Metric::new(
+        source("winter_temp.json"),
+        vec![(State::Maryland, 35.0), (State::Alaska, 5.0)],
+    );
+    let inputs: HashMap<String, Metric> = vec![
+        ("summer_temp.json".to_owned(), summer),
+        ("winter_temp.json".to_owned(), winter),
+    ]
+    .into_iter()
+    .collect();
+    let swing = derived("summer_temp - winter_temp")
+        .evaluate(&inputs, Regions::States)
+        .unwrap();
+    assert_eq!(swing.source().source, "derived.json");
+    assert_eq!(swing.get(State::Maryland), Some(40.0));
+    assert_eq!(swing.values()[&State::Alaska], None);
+    assert_eq!(swing.values()[&State::Virginia], None);
+    assert_eq!(swing.values().len(), 50);
+
+    let error = derived("summer_temp - spring_temp")
+        .evaluate(&inputs, Regions::States)
+        .unwrap_err();
+    assert_eq!(error.to_string(), "derived.json needs spring_temp.json");
+}
+
+#[test]
+fn every_entry_in_derived_json_is_valid() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../derived.json");
+    for derived in Derived::read_all(&path).unwrap() {
+        derived.expression().unwrap();
+    }
+}
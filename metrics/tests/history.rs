@@ -0,0 +1,58 @@
+use metrics::{DataType, History, Metric};
+use states::State;
+
+mod common;
+
+fn history(values: &[(State, &[u16])]) -> History {
+    values
+        .iter()
+        .map(|(state, years)| {
+            let years = years.iter().map(|year| (*year, f64::from(*year))).collect();
+            (*state, years)
+        })
+        .collect()
+}
+
+fn metric(history: History) -> Metric {
+    Metric::with_history(common::source("test.json", DataType::Integer), history)
+}
+
+#[test]
+fn values_are_from_the_latest_year_every_state_has() {
+    let metric = metric(history(&[
+        (State::Maryland, &[2016, 2017, 2018]),
+        (State::Virginia, &[2017, 2018]),
+        (State::Alaska, &[]),
+    ]));
+    assert_eq!(metric.year(), Some(2018));
+    assert_eq!(metric.get(State::Virginia), Some(2018.0));
+    assert_eq!(metric.get(State::Alaska), None);
+    assert_eq!(
+        metric.source().history.as_deref(),
+        Some("test_history.json")
+    );
+}
+
+#[test]
+fn years_missing_from_a_state_are_skipped() {
+    // Every state has data through 2017, but Maryland is missing it
+    let metric = metric(history(&[
+        (State::Maryland, &[2015, 2016, 2018]),
+        (State::Virginia, &[2015, 2016, 2017, 2018]),
+        (State::Alaska, &[2015, 2016, 2017]),
+    ]));
+    assert_eq!(metric.year(), Some(2016));
+    assert_eq!(metric.get(State::Maryland), Some(2016.0));
+    assert_eq!(metric.get(State::Alaska), Some(2016.0));
+}
+
+#[test]
+fn no_year_in_common_has_no_values() {
+    let metric = metric(history(&[
+        (State::Maryland, &[2016]),
+        (State::Virginia, &[2017]),
+    ]));
+    assert_eq!(metric.year(), None);
+    assert_eq!(metric.get(State::Maryland), None);
+    assert_eq!(metric.values().len(), 2);
+}
@@ -0,0 +1,119 @@
+use metrics::{DataType, Item, Items, Metric, Source};
+use states::{Regions, State};
+
+mod common;
+use common::{output, overwrite, problems, read};
+
+/// Counts `items` in Maryland, and nothing in every other state
+fn metric(file: &'static str, items: &[(&str, &str)]) -> Metric {
+    let items: Items = Regions::States
+        .iter()
+        .map(|state| {
+            let items = if state == State::Maryland {
+                items
+                    .iter()
+                    .map(|(id, name)| Item {
+                        id: (*id).to_owned(),
+                        name: (*name).to_owned(),
+                    })
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            (state, items)
+        })
+        .collect();
+    let values: Vec<(State, f64)> = items
+        .iter()
+        .map(|(state, items)| (*state, items.len() as f64))
+        .collect();
+    let source = Source {
+        min: Some(0.0),
+        ..common::source(file, DataType::Integer)
+    };
+    Metric::new(source, values).with_items("park_lists.json", items)
+}
+
+#[test]
+fn metrics_share_an_items_file() {
+    let output = output("metrics_share_an_items_file");
+    let parks = metric(
+        "parks.json",
+        &[("anti", "Antietam"), ("cato", "Catoctin Mountain")],
+    );
+    let battlefields = metric("battlefields.json", &[("anti", "Antietam")]);
+    assert_eq!(parks.source().items.as_deref(), Some("park_lists.json"));
+    output.write(std::slice::from_ref(&parks)).unwrap();
+    output.write(&[battlefields]).unwrap();
+
+    let items = read(&output, "park_lists.json");
+    assert_eq!(items["MD"]["parks"][1]["name"], "Catoctin Mountain");
+    assert_eq!(items["MD"]["battlefields"][0]["id"], "anti");
+    assert_eq!(items["OH"]["battlefields"], serde_json::json!([]));
+    assert_eq!(
+        output
+            .read("battlefields.json")
+            .unwrap()
+            .get(State::Maryland),
+        Some(1.0)
+    );
+    assert_eq!(output.read("parks.json").unwrap(), parks);
+    assert_eq!(problems(&output), Vec::<String>::new());
+}
+
+#[test]
+fn every_item_is_validated() {
+    let output = output("every_item_is_validated");
+    output
+        .write(&[metric("monuments.json", &[("fomc", "Fort McHenry")])])
+        .unwrap();
+    overwrite(
+        &output,
+        "park_lists.json",
+        r#"{"MD": {"monuments": [{"id": "fomc", "name": "Fort McHenry"}, {"id": "hatu"}]}, "OH": {"monuments": 1}, "XX": {}}"#,
+    );
+    let problems = problems(&output);
+    assert_eq!(problems.len(), 3);
+    assert!(problems[0]
+        .starts_with("park_lists.json: MD monuments item 2 is not an item: missing field"));
+    assert_eq!(
+        problems[1..],
+        [
+            "park_lists.json: OH monuments is not a list of items",
+            "park_lists.json: unexpected key XX",
+        ]
+    );
+}
+
+#[test]
+fn states_without_the_key_have_no_items() {
+    let output = output("states_without_the_key_have_no_items");
+    output
+        .write(&[metric("monuments.json", &[("fomc", "Fort McHenry")])])
+        .unwrap();
+    // Another metric's items, and Ohio without any
+    overwrite(
+        &output,
+        "park_lists.json",
+        r#"{"MD": {"monuments": [], "parks": [{"id": "cato", "name": "Catoctin Mountain"}]}, "OH": {}}"#,
+    );
+    let metric = output.read("monuments.json").unwrap();
+    let items = metric.items().unwrap();
+    assert_eq!(items.keys().collect::<Vec<_>>(), [&State::Maryland]);
+    assert_eq!(items[&State::Maryland], []);
+    assert_eq!(problems(&output), Vec::<String>::new());
+}
+
+#[test]
+fn items_are_read_only_if_written() {
+    let output = output("items_are_read_only_if_written");
+    output
+        .write(&[metric("monuments.json", &[("fomc", "Fort McHenry")])])
+        .unwrap();
+    std::fs::remove_file(output.dir().join("park_lists.json")).unwrap();
+    assert_eq!(output.read("monuments.json").unwrap().items(), None);
+
+    overwrite(&output, "park_lists.json", r#"{"MD": {"monuments": 1}}"#);
+    let error = output.read("monuments.json").unwrap_err();
+    assert!(format!("{:#}", error).starts_with("Invalid MD in "));
+}
@@ -0,0 +1,111 @@
+use metrics::{DataType, Metric, Months, Source};
+use states::{Regions, State};
+
+mod common;
+use common::{output, overwrite, problems, read};
+
+fn source(file: &'static str, min: Option<f64>) -> Source {
+    Source {
+        min,
+        ..common::source(file, DataType::Number)
+    }
+}
+
+/// Every state in `regions` has `first` in January, one more each month after that
+fn months(regions: Regions, first: f64) -> Months {
+    regions
+        .iter()
+        .map(|state| {
+            let mut months = [None; 12];
+            for (month, value) in months.iter_mut().enumerate() {
+                *value = Some(first + month as f64);
+            }
+            (state, months)
+        })
+        .collect()
+}
+
+#[test]
+fn values_are_the_average_month() {
+    let mut months = months(Regions::States, 10.0);
+    months.get_mut(&State::Ohio).unwrap()[6] = None;
+    let metric = Metric::with_months(source("high.json", None), "profile.json", months);
+    assert_eq!(metric.get(State::Maryland), Some(15.5));
+    assert_eq!(metric.get(State::Ohio), None);
+    assert_eq!(metric.source().months.as_deref(), Some("profile.json"));
+    assert_eq!(metric.months().unwrap()[&State::Ohio][7], Some(17.0));
+}
+
+#[test]
+fn metrics_share_a_months_file() {
+    let output = output("metrics_share_a_months_file");
+    let high = Metric::with_months(
+        source("high.json", None),
+        "profile.json",
+        months(Regions::States, 40.0),
+    );
+    let low = Metric::with_months(
+        source("low.json", None),
+        "profile.json",
+        months(Regions::States, 20.0),
+    );
+    output.write(std::slice::from_ref(&high)).unwrap();
+    // Written separately, but kept together
+    output.write(&[low]).unwrap();
+
+    let profile = read(&output, "profile.json");
+    assert_eq!(profile["MD"]["high"][0], 40.0);
+    assert_eq!(profile["MD"]["low"][11], 31.0);
+    assert_eq!(output.read("high.json").unwrap(), high);
+    assert_eq!(problems(&output), Vec::<String>::new());
+}
+
+#[test]
+fn states_no_longer_written_are_dropped() {
+    let output = output("states_no_longer_written_are_dropped");
+    let high = |regions| {
+        Metric::with_months(
+            source("high.json", None),
+            "profile.json",
+            months(regions, 40.0),
+        )
+    };
+    let low = Metric::with_months(
+        source("low.json", None),
+        "profile.json",
+        months(Regions::States, 20.0),
+    );
+    output.write(&[high(Regions::All), low]).unwrap();
+    output.write(&[high(Regions::States)]).unwrap();
+
+    let profile = read(&output, "profile.json");
+    assert_eq!(profile["MD"]["high"][0], 40.0);
+    assert_eq!(profile["MD"]["low"][0], 20.0);
+    // Only the high was ever written for Puerto Rico
+    assert_eq!(profile.get("PR"), None);
+    assert_eq!(problems(&output), Vec::<String>::new());
+}
+
+#[test]
+fn every_month_is_validated() {
+    let output = output("every_month_is_validated");
+    let metric = Metric::with_months(
+        source("rain.json", Some(0.0)),
+        "profile.json",
+        months(Regions::States, 1.0),
+    );
+    output.write(&[metric]).unwrap();
+    overwrite(
+        &output,
+        "profile.json",
+        r#"{"MD": {"rain": [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, -12]}, "OH": {"rain": [1]}, "XX": {}}"#,
+    );
+    assert_eq!(
+        problems(&output),
+        [
+            "profile.json: MD rain month 12 is -12, below the minimum of 0",
+            "profile.json: OH rain is not an array of 12 months",
+            "profile.json: unexpected key XX",
+        ]
+    );
+}
@@ -0,0 +1,183 @@
+use std::borrow::Cow;
+use std::path::Path;
+
+use metrics::{land_area, DataType, History, Metric, Normalize, Per, Source};
+use states::{Regions, State};
+
+mod common;
+use common::output;
+
+fn source(file: &'static str, data_type: DataType) -> Source {
+    Source {
+        units: Some(Cow::Borrowed("tons")),
+        min: Some(0.0),
+        max: Some(100.0),
+        ..common::source(file, data_type)
+    }
+}
+
+fn per_capita(file: &'static str) -> Normalize {
+    Normalize {
+        source: Cow::Borrowed(file),
+        per: Per::Capita,
+        scale: 1.0,
+        units: None,
+    }
+}
+
+fn history(values: &[(State, u16, f64)]) -> History {
+    let mut history = History::new();
+    for (state, year, value) in values {
+        history.entry(*state).or_default().insert(*year, *value);
+    }
+    history
+}
+
+#[test]
+fn values_are_divided_by_the_denominator() {
+    let metric = Metric::new(
+        source("emissions.json", DataType::Integer),
+        vec![
+            (State::Maryland, Some(10.0)),
+            (State::Virginia, Some(20.0)),
+            (State::Alaska, None),
+        ],
+    );
+    let population = Metric::new(
+        source("population.json", DataType::Integer),
+        vec![(State::Maryland, 5.0), (State::Virginia, 0.0)],
+    );
+    let normalized = per_capita("emissions.json")
+        .apply(&metric, &population)
+        .unwrap();
+    assert_eq!(normalized.get(State::Maryland), Some(2.0));
+    // Dividing by 0 or a missing value gives null rather than infinity
+    assert_eq!(normalized.values()[&State::Virginia], None);
+    assert_eq!(normalized.values()[&State::Alaska], None);
+
+    let source = normalized.source();
+    assert_eq!(source.source, "emissions_per_capita.json");
+    assert_eq!(source.name, "Test per Person");
+    assert_eq!(source.data_type, DataType::Number);
+    assert_eq!(source.units.as_deref(), Some("tons per person"));
+    assert_eq!(
+        source.description,
+        "Something about each state. Divided by each state's population, in tons per person."
+    );
+    assert_eq!((source.min, source.max), (Some(0.0), None));
+}
+
+#[test]
+fn histories_are_divided_year_by_year() {
+    let metric = Metric::with_history(
+        source("emissions.json", DataType::Number),
+        history(&[
+            (State::Maryland, 2017, 10.0),
+            (State::Maryland, 2018, 12.0),
+            (State::Virginia, 2018, 30.0),
+        ]),
+    );
+    let population = Metric::with_history(
+        source("population.json", DataType::Integer),
+        history(&[
+            (State::Maryland, 2017, 5.0),
+            (State::Maryland, 2018, 6.0),
+            (State::Virginia, 2017, 10.0),
+        ]),
+    );
+    let normalized = Normalize {
+        scale: 1000.0,
+        units: Some(Cow::Borrowed("kilotons per person")),
+        ..per_capita("emissions.json")
+    }
+    .apply(&metric, &population)
+    .unwrap();
+    let maryland = &normalized.history().unwrap()[&State::Maryland];
+    assert_eq!(maryland[&2017], 2000.0);
+    assert_eq!(maryland[&2018], 2000.0);
+    // Virginia has no population for 2018
+    assert!(normalized.history().unwrap()[&State::Virginia].is_empty());
+    assert_eq!(
+        normalized.source().history.as_deref(),
+        Some("emissions_per_capita_history.json")
+    );
+    assert_eq!(
+        normalized.source().units.as_deref(),
+        Some("kilotons per person")
+    );
+}
+
+#[test]
+fn land_area_has_every_region() {
+    let area = land_area(Regions::All);
+    assert_eq!(area.values().len(), 56);
+    assert_eq!(area.get(State::Maryland), Some(9_707.24));
+    let metric = Metric::new(
+        source("parks.json", DataType::Integer),
+        vec![(State::Maryland, 1.0)],
+    );
+    let normalized = Normalize {
+        per: Per::SquareMile,
+        ..per_capita("parks.json")
+    }
+    .apply(&metric, &area)
+    .unwrap();
+    assert_eq!(normalized.source().source, "parks_per_sq_mi.json");
+    assert_eq!(normalized.source().units.as_deref(), Some("tons per sq mi"));
+    assert_eq!(normalized.get(State::Maryland), Some(1.0 / 9_707.24));
+}
+
+#[test]
+fn percents_are_not_normalized() {
+    let metric = Metric::new(
+        source("share.json", DataType::Percent),
+        vec![(State::Maryland, 0.5)],
+    );
+    let error = per_capita("share.json")
+        .apply(&metric, &land_area(Regions::States))
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "share.json is a percent, which can't be divided per capita"
+    );
+}
+
+#[test]
+fn written_metrics_can_be_read_back() {
+    let output = output("written_metrics_can_be_read_back");
+    let metric = Metric::with_history(
+        source("emissions.json", DataType::Integer),
+        history(&[(State::Maryland, 2017, 10.0), (State::Virginia, 2017, 20.0)]),
+    );
+    output.write(std::slice::from_ref(&metric)).unwrap();
+    assert_eq!(output.read("emissions.json").unwrap(), metric);
+    assert!(output.read("other.json").is_err());
+}
+
+#[test]
+fn normalize_json_is_read_in_order() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../normalize.json");
+    let normalize = Normalize::read_all(&path).unwrap();
+    assert_eq!(
+        normalize[0],
+        Normalize {
+            scale: 1_000_000.0,
+            units: Some(Cow::Borrowed("metric tons CO2 per person")),
+            ..per_capita("co2_emissions.json")
+        }
+    );
+    assert_eq!(normalize[1].file(), "national_parks_per_sq_mi.json");
+}
+
+#[test]
+fn scale_and_units_are_optional() {
+    let normalize: Normalize =
+        serde_json::from_str(r#"{"source": "parks.json", "per": "square_mile"}"#).unwrap();
+    assert_eq!(
+        normalize,
+        Normalize {
+            per: Per::SquareMile,
+            ..per_capita("parks.json")
+        }
+    );
+}
@@ -0,0 +1,178 @@
+use metrics::{DataType, Metric, Point, Source};
+use states::{Regions, State};
+
+mod common;
+use common::{output, overwrite, problems, read};
+
+fn point(id: &str, state: State, value: f64) -> Point {
+    Point {
+        id: id.to_owned(),
+        name: format!("{} STATION", id),
+        state,
+        latitude: 39.0,
+        longitude: -77.0,
+        elevation: Some(10.0),
+        value,
+    }
+}
+
+/// The average of its points in Maryland and Ohio, and nothing anywhere else
+fn metric(file: &'static str, points: Vec<Point>) -> Metric {
+    let values = Regions::States.iter().map(|state| {
+        let values: Vec<f64> = points
+            .iter()
+            .filter(|point| point.state == state)
+            .map(|point| point.value)
+            .collect();
+        let value = if values.is_empty() {
+            None
+        } else {
+            Some(values.iter().sum::<f64>() / values.len() as f64)
+        };
+        (state, value)
+    });
+    let source = Source {
+        max: Some(150.0),
+        ..common::source(file, DataType::Number)
+    };
+    Metric::new(source, values).with_points("points.json", points)
+}
+
+#[test]
+fn metrics_share_a_points_file() {
+    let output = output("metrics_share_a_points_file");
+    let summer = metric(
+        "summer.json",
+        vec![
+            point("A", State::Maryland, 80.0),
+            point("B", State::Maryland, 90.0),
+            point("C", State::Ohio, 75.0),
+        ],
+    );
+    // Station C has no winter normal
+    let winter = metric(
+        "winter.json",
+        vec![
+            point("A", State::Maryland, 30.0),
+            point("B", State::Maryland, 40.0),
+        ],
+    );
+    assert_eq!(summer.source().points.as_deref(), Some("points.json"));
+    output.write(std::slice::from_ref(&summer)).unwrap();
+    output.write(&[winter]).unwrap();
+
+    let points = read(&output, "points.json");
+    assert_eq!(points["A"]["name"], "A STATION");
+    assert_eq!(points["A"]["state"], "MD");
+    assert_eq!(points["A"]["values"]["summer"], 80.0);
+    assert_eq!(points["A"]["values"]["winter"], 30.0);
+    assert_eq!(points["C"]["values"]["winter"], serde_json::Value::Null);
+    assert_eq!(output.read("summer.json").unwrap(), summer);
+    assert_eq!(problems(&output), Vec::<String>::new());
+}
+
+#[test]
+fn points_no_longer_written_are_dropped() {
+    let output = output("points_no_longer_written_are_dropped");
+    let summer = |points| metric("summer.json", points);
+    output
+        .write(&[
+            summer(vec![
+                point("A", State::Maryland, 80.0),
+                point("B", State::Ohio, 90.0),
+            ]),
+            metric("winter.json", vec![point("A", State::Maryland, 30.0)]),
+        ])
+        .unwrap();
+    // B closed, and A no longer has a summer normal
+    output
+        .write(&[summer(vec![point("C", State::Ohio, 75.0)])])
+        .unwrap();
+
+    let points = read(&output, "points.json");
+    let ids: Vec<&String> = points.as_object().unwrap().keys().collect();
+    assert_eq!(ids, ["A", "C"]);
+    assert_eq!(points["A"]["values"], serde_json::json!({"winter": 30.0}));
+    assert_eq!(points["C"]["values"], serde_json::json!({"summer": 75.0}));
+    let summer = output.read("summer.json").unwrap();
+    assert_eq!(summer.points().unwrap().len(), 1);
+    assert_eq!(problems(&output), Vec::<String>::new());
+}
+
+#[test]
+fn every_point_is_validated() {
+    let output = output("every_point_is_validated");
+    output
+        .write(&[metric(
+            "summer.json",
+            vec![point("A", State::Maryland, 80.0)],
+        )])
+        .unwrap();
+    overwrite(
+        &output,
+        "points.json",
+        r#"{
+            "A": {"name": "A", "state": "MD", "latitude": 39, "longitude": -77, "values": {"summer": 200}},
+            "B": {"name": "B", "state": "PR", "latitude": 18, "longitude": -66, "values": {"summer": 80}},
+            "C": {"name": "C", "state": "MD", "latitude": -77, "longitude": 390, "values": {"summer": 80}},
+            "D": {"name": "D", "state": "MD", "latitude": 39, "longitude": -77, "values": {"winter": 80}},
+            "E": {"name": "E"}
+        }"#,
+    );
+    let problems = problems(&output);
+    assert_eq!(problems.len(), 4);
+    assert_eq!(
+        problems[..3],
+        [
+            "points.json: A summer is 200, above the maximum of 150",
+            "points.json: B is in unexpected PR",
+            "points.json: C is at -77, 390, which is not a latitude and longitude",
+        ]
+    );
+    assert!(problems[3].starts_with("points.json: E is not a point: missing field"));
+}
+
+#[test]
+fn points_without_the_key_are_left_out() {
+    let output = output("points_without_the_key_are_left_out");
+    let winter = metric(
+        "winter.json",
+        vec![
+            point("A", State::Maryland, 30.0),
+            point("B", State::Maryland, 40.0),
+        ],
+    );
+    output.write(std::slice::from_ref(&winter)).unwrap();
+    // Station C only has a summer normal
+    output
+        .write(&[metric("summer.json", vec![point("C", State::Ohio, 75.0)])])
+        .unwrap();
+    let read = output.read("winter.json").unwrap();
+    let ids: Vec<&str> = read.points().unwrap().iter().map(|p| &*p.id).collect();
+    assert_eq!(ids, ["A", "B"]);
+    assert_eq!(read, winter);
+}
+
+#[test]
+fn a_point_value_must_be_a_number_to_be_read() {
+    let output = output("a_point_value_must_be_a_number_to_be_read");
+    output
+        .write(&[metric(
+            "summer.json",
+            vec![point("A", State::Maryland, 80.0)],
+        )])
+        .unwrap();
+    overwrite(
+        &output,
+        "points.json",
+        r#"{"A": {"name": "A", "state": "MD", "latitude": 39, "longitude": -77, "values": {"summer": "hot"}}}"#,
+    );
+    let error = output.read("summer.json").unwrap_err();
+    assert_eq!(
+        format!("{:#}", error),
+        format!(
+            "Invalid A in {}: \"hot\" is not a number",
+            output.dir().join("points.json").display()
+        )
+    );
+}
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use metrics::{DataType, Metric, Profile, Scaling, Sort, Source, Weight};
+use states::{Regions, State};
+
+mod common;
+
+fn source(file: &'static str) -> Source {
+    common::source(file, DataType::Money)
+}
+
+fn weight(metric: &str, weight: f64, lower_is_better: Option<bool>) -> Weight {
+    Weight {
+        metric: metric.to_owned(),
+        weight,
+        lower_is_better,
+    }
+}
+
+fn profile(scaling: Scaling) -> Profile {
+    Profile {
+        id: "test".to_owned(),
+        name: "Test".to_owned(),
+        description: "A test.".to_owned(),
+        scaling,
+        weights: vec![
+            weight("income.json", 3.0, None),
+            weight("poverty.json", 1.0, Some(true)),
+        ],
+    }
+}
+
+/// Maryland has the highest income and lowest poverty, Virginia is in the middle, Alaska is last
+/// and Wyoming only has an income
+fn inputs() -> HashMap<String, Metric> {
+    let income = Metric::new(
+        source("income.json"),
+        vec![
+            (State::Maryland, 90.0),
+            (State::Virginia, 80.0),
+            (State::Alaska, 70.0),
+            (State::Wyoming, 80.0),
+        ],
+    );
+    let poverty = Metric::new(
+        source("poverty.json"),
+        vec![
+            (State::Maryland, 0.05),
+            (State::Virginia, 0.10),
+            (State::Alaska, 0.15),
+        ],
+    );
+    vec![
+        ("income.json".to_owned(), income),
+        ("poverty.json".to_owned(), poverty),
+    ]
+    .into_iter()
+    .collect()
+}
+
+fn close(actual: Option<f64>, expected: f64) -> bool {
+    actual.is_some_and(|actual| (actual - expected).abs() < 1e-9)
+}
+
+#[test]
+fn min_max_scores_are_weighted_averages() {
+    let [score, rank] = profile(Scaling::MinMax)
+        .score(&inputs(), Regions::States)
+        .unwrap();
+    assert!(close(score.get(State::Maryland), 1.0));
+    assert!(close(score.get(State::Virginia), 0.5));
+    assert!(close(score.get(State::Alaska), 0.0));
+    // Only income counts for Wyoming
+    assert!(close(score.get(State::Wyoming), 0.5));
+    assert_eq!(score.values()[&State::Ohio], None);
+    assert_eq!(score.values().len(), 50);
+
+    assert_eq!(rank.get(State::Maryland), Some(1.0));
+    assert_eq!(rank.get(State::Virginia), Some(2.0));
+    assert_eq!(rank.get(State::Wyoming), Some(2.0));
+    assert_eq!(rank.get(State::Alaska), Some(4.0));
+    assert_eq!(rank.values()[&State::Ohio], None);
+}
+
+#[test]
+fn z_scores_flip_lower_is_better() {
+    let [score, rank] = profile(Scaling::ZScore)
+        .score(&inputs(), Regions::States)
+        .unwrap();
+    // Income's standard deviation is √50 and poverty's is √(1/600)
+    let income = 10.0 / 50f64.sqrt();
+    let poverty = 0.05 / (1.0f64 / 600.0).sqrt();
+    assert!(close(
+        score.get(State::Maryland),
+        (income * 3.0 + poverty) / 4.0
+    ));
+    assert!(close(score.get(State::Virginia), 0.0));
+    assert!(close(
+        score.get(State::Alaska),
+        -(income * 3.0 + poverty) / 4.0
+    ));
+    assert_eq!(rank.get(State::Maryland), Some(1.0));
+    assert_eq!(rank.get(State::Alaska), Some(4.0));
+}
+
+#[test]
+fn sources_describe_the_profile() {
+    let [score, rank] = profile(Scaling::MinMax)
+        .score(&inputs(), Regions::States)
+        .unwrap();
+    let source = score.source();
+    assert_eq!(source.source, "score_test.json");
+    assert_eq!(source.name, "Test Score");
+    assert_eq!(source.data_type, DataType::Percent);
+    assert_eq!((source.min, source.max), (Some(0.0), Some(1.0)));
+    assert!(source
+        .description
+        .ends_with("weighted across income, poverty."));
+    assert_eq!(rank.source().source, "score_test_rank.json");
+    assert_eq!(rank.source().data_type, DataType::Integer);
+    assert_eq!(rank.to_json()["MD"], 1);
+
+    let error = Profile {
+        weights: vec![weight("missing.json", 1.0, None)],
+        ..profile(Scaling::MinMax)
+    }
+    .score(&inputs(), Regions::States)
+    .unwrap_err();
+    assert_eq!(error.to_string(), "Profile test needs missing.json");
+}
+
+#[test]
+fn every_profile_in_the_repo_is_valid() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../profiles");
+    let profiles = Profile::read_all(&dir).unwrap();
+    assert!(profiles.iter().any(|profile| profile.id == "livability"));
+    let sources = std::fs::read_to_string(dir.join("../sources.json")).unwrap();
+    for profile in profiles {
+        assert!(!profile.weights.is_empty());
+        for file in profile.files() {
+            assert!(
+                sources.contains(&format!("\"{}\"", file)),
+                "{} isn't in sources.json",
+                file
+            );
+        }
+    }
+}
+
+#[test]
+fn lower_is_better_defaults_to_the_sort() {
+    let mut inputs = inputs();
+    let poverty = &inputs["poverty.json"];
+    let ascending = Metric::new(
+        Source {
+            sort: Sort::Ascending,
+            ..poverty.source().clone()
+        },
+        poverty.values().clone(),
+    );
+    inputs.insert("poverty.json".to_owned(), ascending);
+    let explicit = profile(Scaling::MinMax);
+    let default = Profile {
+        weights: vec![
+            weight("income.json", 3.0, None),
+            weight("poverty.json", 1.0, None),
+        ],
+        ..explicit.clone()
+    };
+    assert_eq!(
+        default.score(&inputs, Regions::States).unwrap(),
+        explicit.score(&inputs, Regions::States).unwrap()
+    );
+}
@@ -0,0 +1,142 @@
+use metrics::{DataType, Metric, Sort, Source, Stats};
+use states::{Regions, State};
+
+mod common;
+use common::{output, overwrite, problems, read};
+
+fn source(sort: Sort) -> Source {
+    Source {
+        sort,
+        ..common::source("poverty.json", DataType::Percent)
+    }
+}
+
+fn poverty(sort: Sort) -> Metric {
+    Metric::new(
+        source(sort),
+        vec![
+            (State::Maryland, Some(0.09)),
+            (State::Virginia, Some(0.10)),
+            (State::Alaska, Some(0.10)),
+            (State::Wyoming, Some(0.13)),
+            (State::Ohio, None),
+        ],
+    )
+}
+
+#[test]
+fn ties_share_a_rank() {
+    let stats = poverty(Sort::Ascending).stats();
+    assert_eq!(stats.rank[&State::Maryland], Some(1));
+    assert_eq!(stats.rank[&State::Virginia], Some(2));
+    assert_eq!(stats.rank[&State::Alaska], Some(2));
+    assert_eq!(stats.rank[&State::Wyoming], Some(4));
+    assert_eq!(stats.rank[&State::Ohio], None);
+
+    let stats = poverty(Sort::Descending).stats();
+    assert_eq!(stats.rank[&State::Wyoming], Some(1));
+    assert_eq!(stats.rank[&State::Alaska], Some(2));
+    assert_eq!(stats.rank[&State::Maryland], Some(4));
+}
+
+#[test]
+fn percentiles_run_from_last_to_first() {
+    let stats = poverty(Sort::Ascending).stats();
+    assert_eq!(stats.percentile[&State::Maryland], Some(1.0));
+    assert_eq!(stats.percentile[&State::Virginia], Some(2.0 / 3.0));
+    assert_eq!(stats.percentile[&State::Wyoming], Some(0.0));
+    assert_eq!(stats.percentile[&State::Ohio], None);
+}
+
+#[test]
+fn mean_and_median_skip_missing_values() {
+    let stats = poverty(Sort::Ascending).stats();
+    assert_eq!(stats.count, 4);
+    assert!((stats.mean.unwrap() - 0.105).abs() < 1e-9);
+    assert_eq!(stats.median, Some(0.10));
+
+    let odd = Metric::new(
+        source(Sort::Ascending),
+        vec![
+            (State::Maryland, 3.0),
+            (State::Ohio, 1.0),
+            (State::Utah, 8.0),
+        ],
+    );
+    assert_eq!(odd.stats().median, Some(3.0));
+
+    let empty = Stats::new(&Default::default(), Sort::Ascending);
+    assert_eq!((empty.count, empty.mean, empty.median), (0, None, None));
+}
+
+#[test]
+fn stats_are_written_and_validated() {
+    let output = output("stats_are_written_and_validated");
+    let metric = Metric::new(
+        source(Sort::Ascending),
+        Regions::States.iter().map(|state| {
+            (
+                state,
+                if state == State::Ohio {
+                    None
+                } else {
+                    Some(0.1)
+                },
+            )
+        }),
+    );
+    output.write(&[metric]).unwrap();
+
+    let stats = read(&output, "poverty_stats.json");
+    assert_eq!(stats["count"], 49);
+    assert_eq!(stats["median"], 0.1);
+    assert_eq!(stats["rank"]["MD"], 1);
+    assert_eq!(stats["rank"]["OH"], serde_json::Value::Null);
+    assert_eq!(stats["percentile"]["MD"], 1.0);
+    assert_eq!(problems(&output), Vec::<String>::new());
+
+    overwrite(
+        &output,
+        "poverty_stats.json",
+        r#"{"count": 1, "rank": {"MD": 2}, "percentile": {}}"#,
+    );
+    let problems = problems(&output);
+    assert_eq!(problems.len(), 3);
+    assert!(problems[0].starts_with("poverty_stats.json: rank missing AL, AK,"));
+    assert_eq!(
+        problems[1],
+        "poverty_stats.json: MD rank is 2, expected 1 to 1"
+    );
+    assert!(problems[2].starts_with("poverty_stats.json: percentile missing AL,"));
+}
+
+#[test]
+fn samples_are_written_and_validated() {
+    let output = output("samples_are_written_and_validated");
+    let metric = Metric::new(
+        source(Sort::Ascending),
+        Regions::States.iter().map(|state| (state, 0.1)),
+    )
+    .with_samples(vec![(State::Maryland, 12), (State::Ohio, 1)]);
+    assert_eq!(metric.stats().samples.unwrap()[&State::Maryland], 12);
+    output.write(&[metric]).unwrap();
+
+    let mut stats = read(&output, "poverty_stats.json");
+    assert_eq!(stats["samples"]["MD"], 12);
+    assert_eq!(stats["samples"]["OH"], 1);
+    assert_eq!(problems(&output), Vec::<String>::new());
+
+    // Without samples there's no key at all
+    let plain = poverty(Sort::Ascending).stats().to_json();
+    assert!(plain.get("samples").is_none());
+
+    stats["samples"] = serde_json::json!({"MD": 1.5, "PR": 2});
+    overwrite(&output, "poverty_stats.json", &stats.to_string());
+    assert_eq!(
+        problems(&output),
+        [
+            "poverty_stats.json: MD samples is 1.5, expected a whole number",
+            "poverty_stats.json: samples for PR, not in REGIONS",
+        ]
+    );
+}
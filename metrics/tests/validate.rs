@@ -0,0 +1,254 @@
+use std::fs;
+
+use serde_json::{json, Value};
+
+use metrics::{DataType, History, Metric, Months, Source};
+use states::Regions;
+
+mod common;
+use common::{output, overwrite, problems, read};
+
+/// Every state at `value`
+fn metric(source: Source, value: f64) -> Metric {
+    Metric::new(source, Regions::States.iter().map(|state| (state, value)))
+}
+
+/// The problems with `source` written for every state, then Maryland's value replaced by
+/// `maryland`
+fn problems_with(name: &str, source: Source, maryland: Value) -> Vec<String> {
+    let output = output(name);
+    let file = source.source.to_string();
+    output.write(&[metric(source, 0.5)]).unwrap();
+    let mut values = read(&output, &file);
+    values["MD"] = maryland;
+    overwrite(&output, &file, &values.to_string());
+    problems(&output)
+}
+
+#[test]
+fn written_metrics_are_valid() {
+    let output = output("written_metrics_are_valid");
+    output
+        .write(&[metric(common::source("test.json", DataType::Number), 1.0)])
+        .unwrap();
+    assert_eq!(problems(&output), Vec::<String>::new());
+}
+
+#[test]
+fn every_state_and_nothing_else() {
+    let output = output("every_state_and_nothing_else");
+    output
+        .write(&[metric(common::source("test.json", DataType::Number), 1.0)])
+        .unwrap();
+    let mut values = read(&output, "test.json");
+    values.as_object_mut().unwrap().remove("OH");
+    values.as_object_mut().unwrap().remove("WY");
+    values["XX"] = json!(1.0);
+    values["DC"] = json!(1.0);
+    overwrite(&output, "test.json", &values.to_string());
+    assert_eq!(
+        problems(&output),
+        [
+            "test.json: missing OH, WY",
+            "test.json: unexpected key DC",
+            "test.json: unexpected key XX",
+        ]
+    );
+}
+
+#[test]
+fn nulls_are_valid() {
+    let source = common::source("nulls.json", DataType::Money);
+    assert_eq!(
+        problems_with("nulls_are_valid", source, Value::Null),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn values_must_be_numbers() {
+    let source = common::source("strings.json", DataType::Number);
+    assert_eq!(
+        problems_with("values_must_be_numbers", source, json!("1.5")),
+        ["strings.json: MD is \"1.5\", expected a number or null"]
+    );
+}
+
+#[test]
+fn integers_must_be_whole() {
+    let source = || common::source("integer.json", DataType::Integer);
+    assert_eq!(
+        problems_with("integers_must_be_whole", source(), json!(2.5)),
+        ["integer.json: MD is 2.5, expected an integer"]
+    );
+    assert_eq!(
+        problems_with("integers_can_be_whole", source(), json!(2)),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn percents_are_fractions() {
+    let source = || common::source("percent.json", DataType::Percent);
+    assert_eq!(
+        problems_with("percents_above_one", source(), json!(1.5)),
+        ["percent.json: MD is 1.5, above the maximum of 1"]
+    );
+    assert_eq!(
+        problems_with("percents_below_minus_one", source(), json!(-1.5)),
+        ["percent.json: MD is -1.5, below the minimum of -1"]
+    );
+    assert_eq!(
+        problems_with("percents_can_be_negative", source(), json!(-0.5)),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn money_is_never_negative() {
+    let source = || common::source("money.json", DataType::Money);
+    assert_eq!(
+        problems_with("money_is_never_negative", source(), json!(-0.01)),
+        ["money.json: MD is -0.01, below the minimum of 0"]
+    );
+    assert_eq!(
+        problems_with("money_can_be_zero", source(), json!(0)),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn sources_override_the_range() {
+    // A percent which isn't a fraction, like a percentage point change
+    let points = Source {
+        max: Some(100.0),
+        ..common::source("points.json", DataType::Percent)
+    };
+    assert_eq!(
+        problems_with("a_source_max_overrides_one", points, json!(50)),
+        Vec::<String>::new()
+    );
+    let balance = Source {
+        min: Some(-100.0),
+        ..common::source("balance.json", DataType::Money)
+    };
+    assert_eq!(
+        problems_with("a_source_min_overrides_zero", balance, json!(-50)),
+        Vec::<String>::new()
+    );
+    let rank = Source {
+        min: Some(1.0),
+        max: Some(50.0),
+        ..common::source("rank.json", DataType::Integer)
+    };
+    assert_eq!(
+        problems_with("a_source_max_is_checked", rank.clone(), json!(51)),
+        ["rank.json: MD is 51, above the maximum of 50"]
+    );
+    assert_eq!(
+        problems_with("a_source_min_is_checked", rank, json!(0)),
+        ["rank.json: MD is 0, below the minimum of 1"]
+    );
+}
+
+#[test]
+fn every_year_of_history_is_checked() {
+    let output = output("every_year_of_history_is_checked");
+    let history: History = Regions::States
+        .iter()
+        .map(|state| (state, vec![(2019, 0.25), (2020, 0.5)].into_iter().collect()))
+        .collect();
+    let source = common::source("share.json", DataType::Percent);
+    output
+        .write(&[Metric::with_history(source, history)])
+        .unwrap();
+    assert_eq!(problems(&output), Vec::<String>::new());
+
+    // States may be missing from the history, but every year there must be valid
+    overwrite(
+        &output,
+        "share_history.json",
+        r#"{"MD": {"2019": 0.25, "2020": 2}, "OH": {"last": 0.5}, "WY": 0.5, "XX": {}}"#,
+    );
+    assert_eq!(
+        problems(&output),
+        [
+            "share_history.json: MD 2020 is 2, above the maximum of 1",
+            "share_history.json: OH has an invalid year last",
+            "share_history.json: WY is not an object of years",
+            "share_history.json: unexpected key XX",
+        ]
+    );
+}
+
+#[test]
+fn side_files_are_checked() {
+    let output = output("side_files_are_checked");
+    let months: Months = Regions::States
+        .iter()
+        .map(|state| (state, [Some(0.5); 12]))
+        .collect();
+    let source = common::source("sunny.json", DataType::Percent);
+    output
+        .write(&[Metric::with_months(source, "profile.json", months)])
+        .unwrap();
+    assert_eq!(problems(&output), Vec::<String>::new());
+
+    let mut profile = read(&output, "profile.json");
+    profile["MD"]["sunny"][6] = json!(1.5);
+    profile["OH"]["sunny"] = json!([0.5]);
+    profile["XX"] = json!({});
+    overwrite(&output, "profile.json", &profile.to_string());
+    assert_eq!(
+        problems(&output),
+        [
+            "profile.json: MD sunny month 7 is 1.5, above the maximum of 1",
+            "profile.json: OH sunny is not an array of 12 months",
+            "profile.json: unexpected key XX",
+        ]
+    );
+}
+
+#[test]
+fn stats_are_checked() {
+    let output = output("stats_are_checked");
+    let metric = metric(common::source("stats.json", DataType::Number), 1.0)
+        .with_samples(Regions::States.iter().map(|state| (state, 2)));
+    output.write(&[metric]).unwrap();
+    assert_eq!(problems(&output), Vec::<String>::new());
+
+    let mut stats = read(&output, "stats_stats.json");
+    stats["rank"]["MD"] = json!(51);
+    stats["percentile"]["OH"] = json!(-0.5);
+    stats["percentile"].as_object_mut().unwrap().remove("WY");
+    stats["samples"]["MD"] = json!(1.5);
+    stats["samples"]["XX"] = json!(1);
+    overwrite(&output, "stats_stats.json", &stats.to_string());
+    assert_eq!(
+        problems(&output),
+        [
+            "stats_stats.json: MD rank is 51, expected 1 to 50",
+            "stats_stats.json: percentile missing WY",
+            "stats_stats.json: OH percentile is -0.5, expected 0 to 1",
+            "stats_stats.json: MD samples is 1.5, expected a whole number",
+            "stats_stats.json: samples for XX, not in REGIONS",
+        ]
+    );
+
+    overwrite(&output, "stats_stats.json", r#"{"rank": {}}"#);
+    assert_eq!(problems(&output), ["stats_stats.json: missing count"]);
+}
+
+#[test]
+fn unreadable_files_are_reported() {
+    let output = output("unreadable_files_are_reported");
+    output
+        .write(&[metric(common::source("broken.json", DataType::Number), 1.0)])
+        .unwrap();
+    overwrite(&output, "broken.json", "[1, 2]");
+    fs::remove_file(output.dir().join("broken_stats.json")).unwrap();
+    let problems = problems(&output);
+    assert_eq!(problems.len(), 2);
+    assert!(problems[0].starts_with("broken.json: not an object of state codes: "));
+    assert!(problems[1].starts_with("broken_stats.json: could not read "));
+}
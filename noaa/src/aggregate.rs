@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+
+use collector::Http;
+use states::State;
+
+/// How every station's normal in a state becomes the state's value. Selected at runtime with the
+/// `NOAA_AGGREGATION` env var, which can be "mean" (the default), "median", or "population".
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Aggregation {
+    /// The average of every station, so regions with lots of stations count for more
+    #[default]
+    Mean,
+    /// The middle station, so a few stations on mountain tops don't drag the state down
+    Median,
+    /// Each county's population goes to the station closest to it, then stations are averaged by
+    /// how many people they stand for
+    Population,
+}
+
+impl Aggregation {
+    pub fn from_env() -> Result<Self, ParseAggregationError> {
+        match env::var("NOAA_AGGREGATION") {
+            Ok(value) => value.parse(),
+            Err(_) => Ok(Aggregation::default()),
+        }
+    }
+
+    /// Appended to each metric's description
+    pub(crate) fn description(self) -> &'static str {
+        match self {
+            Aggregation::Mean => "Averaged across every station in the state.",
+            Aggregation::Median => "The median of every station in the state.",
+            Aggregation::Population => {
+                "Averaged across every station in the state, weighted by the population of the counties closest to each."
+            }
+        }
+    }
+
+    /// The state's value from every reading, `None` if there are none. `counties` are only used
+    /// for [`Aggregation::Population`], along with where each of the `stations` is.
+    pub(crate) fn aggregate(
+        self,
+        readings: &[Reading],
+        stations: &HashMap<String, Station>,
+        counties: &[County],
+    ) -> Option<f64> {
+        if readings.is_empty() {
+            return None;
+        }
+        match self {
+            Aggregation::Mean => Some(
+                readings.iter().map(|reading| reading.value).sum::<f64>() / readings.len() as f64,
+            ),
+            Aggregation::Median => {
+                let mut values: Vec<f64> = readings.iter().map(|reading| reading.value).collect();
+                values.sort_by(|a, b| a.total_cmp(b));
+                let middle = values.len() / 2;
+                if values.len() % 2 == 0 {
+                    Some((values[middle - 1] + values[middle]) / 2.0)
+                } else {
+                    Some(values[middle])
+                }
+            }
+            Aggregation::Population => {
+                let located: Vec<(&Reading, &Location)> = readings
+                    .iter()
+                    .filter_map(|reading| {
+                        Some((reading, &stations.get(&reading.station)?.location))
+                    })
+                    .collect();
+                let mut weights = vec![0.0; located.len()];
+                for county in counties {
+                    let closest = located
+                        .iter()
+                        .map(|(_, location)| distance(location, &county.location))
+                        .enumerate()
+                        .min_by(|(_, a), (_, b)| a.total_cmp(b));
+                    if let Some((index, _)) = closest {
+                        weights[index] += county.population;
+                    }
+                }
+                let total: f64 = weights.iter().sum();
+                if total == 0.0 {
+                    return None;
+                }
+                let weighted: f64 = located
+                    .iter()
+                    .zip(&weights)
+                    .map(|((reading, _), weight)| reading.value * weight)
+                    .sum();
+                Some(weighted / total)
+            }
+        }
+    }
+}
+
+/// Returned when `NOAA_AGGREGATION` is set to something other than "mean", "median", or
+/// "population".
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseAggregationError(String);
+
+impl fmt::Display for ParseAggregationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} is not a valid NOAA aggregation, expected \"mean\", \"median\", or \"population\"",
+            self.0
+        )
+    }
+}
+
+impl Error for ParseAggregationError {}
+
+impl FromStr for Aggregation {
+    type Err = ParseAggregationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "mean" => Ok(Aggregation::Mean),
+            "median" => Ok(Aggregation::Median),
+            "population" => Ok(Aggregation::Population),
+            _ => Err(ParseAggregationError(s.to_owned())),
+        }
+    }
+}
+
+/// One station's normal
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Reading {
+    pub(crate) station: String,
+    pub(crate) value: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Location {
+    pub(crate) latitude: f64,
+    pub(crate) longitude: f64,
+}
+
+/// Where a station is, from NOAA's station metadata
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Station {
+    pub(crate) name: String,
+    pub(crate) state: State,
+    pub(crate) location: Location,
+    /// In meters
+    pub(crate) elevation: Option<f64>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct County {
+    pub(crate) population: f64,
+    /// The Census Bureau's internal point, roughly the middle of the county
+    pub(crate) location: Location,
+}
+
+/// Great circle distance in km
+fn distance(a: &Location, b: &Location) -> f64 {
+    let (lat_a, lat_b) = (a.latitude.to_radians(), b.latitude.to_radians());
+    let d_lat = lat_b - lat_a;
+    let d_lon = (b.longitude - a.longitude).to_radians();
+    let h = (d_lat / 2.0).sin().powi(2) + lat_a.cos() * lat_b.cos() * (d_lon / 2.0).sin().powi(2);
+    2.0 * 6371.0 * h.sqrt().asin()
+}
+
+const COUNTIES_URL: &str =
+    "https://api.census.gov/data/2020/dec/pl?get=P1_001N,INTPTLAT,INTPTLON&for=county:*";
+
+/// Population and location of every county from the 2020 Census, by state
+pub(crate) async fn get_counties(http: &Http) -> Result<HashMap<State, Vec<County>>> {
+    let rows: Vec<Vec<String>> = http.get_json(COUNTIES_URL, &[]).await?;
+    let mut counties: HashMap<State, Vec<County>> = HashMap::new();
+    // The first row is the header
+    for row in rows.iter().skip(1) {
+        let field = |index: usize| {
+            row.get(index)
+                .ok_or_else(|| anyhow!("Census county row is too short: {:?}", row))
+        };
+        let state = match field(3)?.parse().ok().and_then(State::from_fips) {
+            Some(state) => state,
+            None => continue,
+        };
+        let number = |index: usize| -> Result<f64> {
+            let value = field(index)?;
+            value
+                .parse()
+                .map_err(|_| anyhow!("Invalid number {} in Census county row {:?}", value, row))
+        };
+        counties.entry(state).or_default().push(County {
+            population: number(0)?,
+            location: Location {
+                latitude: number(1)?,
+                longitude: number(2)?,
+            },
+        });
+    }
+    Ok(counties)
+}
@@ -0,0 +1,79 @@
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Where the normals come from. Selected at runtime with the `NOAA_DATASET` env var, which can be
+/// "cdo" (the default), "access", or "csv".
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum Dataset {
+    /// The 1981–2010 normals from the Climate Data Online API, which needs a NOAA_TOKEN
+    #[default]
+    Cdo,
+    /// The 1991–2020 normals from NCEI's data access service, every station at once
+    Access,
+    /// The 1991–2020 normals from the bulk by-station CSV files, downloaded and extracted into
+    /// "annualseasonal" and "monthly" in this dir (`NOAA_NORMALS_DIR`,
+    /// raw_data/noaa/normals-1991-2020 by default)
+    Csv(PathBuf),
+}
+
+impl Dataset {
+    pub fn from_env() -> Result<Self, ParseDatasetError> {
+        match env::var("NOAA_DATASET") {
+            Ok(value) => value.parse(),
+            Err(_) => Ok(Dataset::default()),
+        }
+    }
+
+    /// Which years the normals are averaged over
+    pub(crate) fn years(&self) -> &'static str {
+        match self {
+            Dataset::Cdo => "1981 to 2010",
+            Dataset::Access | Dataset::Csv(_) => "1991 to 2020",
+        }
+    }
+
+    pub(crate) fn source_url(&self) -> &'static str {
+        match self {
+            Dataset::Cdo => "https://www.ncdc.noaa.gov/cdo-web/datasets",
+            Dataset::Access | Dataset::Csv(_) => {
+                "https://www.ncei.noaa.gov/products/land-based-station/us-climate-normals"
+            }
+        }
+    }
+}
+
+/// Returned when `NOAA_DATASET` is set to something other than "cdo", "access", or "csv".
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseDatasetError(String);
+
+impl fmt::Display for ParseDatasetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} is not a valid NOAA dataset, expected \"cdo\", \"access\", or \"csv\"",
+            self.0
+        )
+    }
+}
+
+impl Error for ParseDatasetError {}
+
+impl FromStr for Dataset {
+    type Err = ParseDatasetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "cdo" => Ok(Dataset::Cdo),
+            "access" => Ok(Dataset::Access),
+            "csv" => Ok(Dataset::Csv(
+                env::var("NOAA_NORMALS_DIR")
+                    .unwrap_or_else(|_| "raw_data/noaa/normals-1991-2020".to_owned())
+                    .into(),
+            )),
+            _ => Err(ParseDatasetError(s.to_owned())),
+        }
+    }
+}
@@ -0,0 +1,639 @@
+#![forbid(unsafe_code)]
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fmt::Debug;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use futures::future::{join_all, try_join};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use collector::{fetch_pages, Collector, Http, Page};
+use metrics::{DataType, Metric, Months, Point, Sort, Source};
+use states::{Regions, Resolver, State};
+
+use aggregate::{get_counties, County, Location, Reading, Station};
+pub use aggregate::{Aggregation, ParseAggregationError};
+pub use dataset::{Dataset, ParseDatasetError};
+use normals::{Normal, CLIMATE_PROFILE, MONTHLY_NORMALS, NORMALS};
+
+mod aggregate;
+mod dataset;
+mod ncei;
+mod normals;
+
+type Code = State;
+type ID = String;
+
+/// Every station's readings of each normal, for each period (the year, or every month), by state
+type Readings = Vec<Vec<HashMap<Code, Vec<Reading>>>>;
+
+/// Each state's readings of one normal for one period, or why the request failed
+type Fetched = Vec<(Code, Result<Vec<Reading>, String>)>;
+
+/// Why states have nothing for a normal, when something went wrong fetching it
+type Failures = HashMap<Code, String>;
+
+/// Every station behind each state's value of [`NORMALS`], written when configured with
+/// [`Noaa::with_station_file`]
+pub const STATION_FILE: &str = "noaa_stations.json";
+
+const GET_STATES_URI: &str =
+    "https://www.ncdc.noaa.gov/cdo-web/api/v2/locations?datasetid=NORMAL_ANN&locationcategoryid=ST&limit=1000";
+
+#[derive(Debug, Deserialize)]
+struct StateData {
+    name: String,
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Data {
+    station: String,
+    value: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct StationData {
+    id: String,
+    name: String,
+    latitude: f64,
+    longitude: f64,
+    /// In meters
+    elevation: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GetResponse<T> {
+    /// Only there when asked for (which is the default)
+    metadata: Option<Metadata>,
+    results: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Metadata {
+    resultset: ResultSet,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResultSet {
+    /// Of every page
+    count: usize,
+}
+
+/// Every result of `url`, which has a `limit` but no `offset`, fetching as many pages as NOAA's
+/// count says there are
+async fn get_all<T: DeserializeOwned>(url: &str, token: &str, http: &Http) -> Result<Vec<T>> {
+    fetch_pages(&format!("results from {}", url), |offset| async move {
+        // Offsets start at 1
+        let page_url = format!("{}&offset={}", url, offset + 1);
+        let page: GetResponse<T> = http.get_json(&page_url, &[("token", token)]).await?;
+        // Without metadata, this page is all there is
+        let total = page
+            .metadata
+            .map_or(offset + page.results.len(), |metadata| {
+                metadata.resultset.count
+            });
+        Ok(Page {
+            total,
+            records: page.results,
+        })
+    })
+    .await
+}
+
+/// Gets states IDs as needed by NOAA
+async fn get_states(token: &str, http: &Http) -> Result<HashMap<ID, Code>> {
+    let mut resolver = Resolver::new();
+    let states = get_all::<StateData>(GET_STATES_URI, token, http)
+        .await?
+        .into_iter()
+        .filter_map(|state| Some((state.id.to_owned(), resolver.resolve(&state.name)?)))
+        .collect::<HashMap<ID, Code>>();
+    resolver.warn_unresolved(GET_STATES_URI);
+    Ok(states)
+}
+
+const DATA_URL: &str = "https://www.ncdc.noaa.gov/cdo-web/api/v2/data?datasetid=NORMAL_ANN&startdate=2000-01-01&enddate=2010-01-01&units=standard&limit=1000";
+/// Followed by the start and end dates of the month, which is requested one at a time
+const MONTHLY_URL: &str =
+    "https://www.ncdc.noaa.gov/cdo-web/api/v2/data?datasetid=NORMAL_MLY&units=standard&limit=1000";
+
+/// Every station's reading of a specific type in a state from `url` in NOAA's API
+async fn data_request(
+    token: &str,
+    url: &str,
+    state_id: &ID,
+    state: Code,
+    data_type: &str,
+    http: &Http,
+) -> Result<Vec<Reading>> {
+    let url = format!("{}&locationid={}&datatypeid={}", url, state_id, data_type);
+    let data = get_all::<Data>(&url, token, http)
+        .await
+        .with_context(|| format!("Could not fetch {} for {} ({})", data_type, state, state_id))?;
+    Ok(data
+        .into_iter()
+        .map(|data| Reading {
+            station: data.station,
+            value: data.value,
+        })
+        .collect())
+}
+
+/// Get the readings of a specific data type for all states, see [`tolerate`]
+async fn get_readings(
+    token: &str,
+    url: &str,
+    data_type: &str,
+    states: &HashMap<ID, Code>,
+    http: &Http,
+) -> Result<Fetched> {
+    tolerate(
+        join_all(states.iter().map(|(id, state)| async move {
+            (
+                *state,
+                data_request(token, url, id, *state, data_type, http).await,
+            )
+        }))
+        .await,
+    )
+}
+
+/// Each state's response, or why there isn't one (which is logged) so one bad state doesn't take
+/// the rest down with it. Only fails if every state did, since then nothing is left.
+fn tolerate<T>(responses: Vec<(Code, Result<T>)>) -> Result<Vec<(Code, Result<T, String>)>> {
+    let all_failed = responses.iter().all(|(_, response)| response.is_err());
+    let mut tolerated = Vec::with_capacity(responses.len());
+    for (state, response) in responses {
+        match response {
+            Ok(response) => tolerated.push((state, Ok(response))),
+            Err(error) if all_failed => return Err(error),
+            Err(error) => {
+                eprintln!("Warning: {:#}", error);
+                tolerated.push((state, Err(format!("{:#}", error))));
+            }
+        }
+    }
+    Ok(tolerated)
+}
+
+const STATIONS_URL: &str =
+    "https://www.ncdc.noaa.gov/cdo-web/api/v2/stations?datasetid=NORMAL_ANN&limit=1000";
+
+/// Every station with normals in every state: its name, where it is, and its elevation. Also why
+/// the stations of any state are unknown, see [`tolerate`].
+async fn get_stations(
+    token: &str,
+    states: &HashMap<ID, Code>,
+    http: &Http,
+) -> Result<(HashMap<String, Station>, Failures)> {
+    let responses = tolerate(
+        join_all(states.iter().map(|(id, state)| async move {
+            let url = format!("{}&locationid={}", STATIONS_URL, id);
+            let response = get_all::<StationData>(&url, token, http)
+                .await
+                .with_context(|| format!("Could not fetch stations for {} ({})", state, id));
+            (*state, response)
+        }))
+        .await,
+    )?;
+    let mut failures = Failures::new();
+    let mut located = HashMap::new();
+    for (state, stations) in responses {
+        let stations = match stations {
+            Ok(stations) => stations,
+            Err(reason) => {
+                failures.insert(state, reason);
+                continue;
+            }
+        };
+        located.extend(stations.into_iter().map(|station| {
+            let metadata = Station {
+                name: station.name,
+                state,
+                location: Location {
+                    latitude: station.latitude,
+                    longitude: station.longitude,
+                },
+                elevation: station.elevation,
+            };
+            (station.id, metadata)
+        }));
+    }
+    Ok((located, failures))
+}
+
+/// Every reading with the metadata of its station
+fn points(
+    readings: &HashMap<Code, Vec<Reading>>,
+    stations: &HashMap<String, Station>,
+) -> Vec<Point> {
+    let mut points: Vec<Point> = readings
+        .values()
+        .flatten()
+        .filter_map(|reading| {
+            let station = stations.get(&reading.station)?;
+            Some(Point {
+                id: reading.station.clone(),
+                name: station.name.clone(),
+                state: station.state,
+                latitude: station.location.latitude,
+                longitude: station.location.longitude,
+                elevation: station.elevation,
+                value: reading.value,
+            })
+        })
+        .collect();
+    points.sort_by(|a, b| a.id.cmp(&b.id));
+    points
+}
+
+/// Everything fetched for the [`Dataset`], including every station's metadata (which the 1991–2020
+/// datasets have alongside their normals). Counties are only fetched for
+/// [`Aggregation::Population`].
+pub struct Raw {
+    normals: RawNormals,
+    monthly: RawNormals,
+    stations: HashMap<String, Station>,
+    /// States whose stations couldn't be fetched, so they can't be weighted by population
+    unlocated: Failures,
+    counties: HashMap<Code, Vec<County>>,
+}
+
+impl Raw {
+    /// The CSVs of the 1991–2020 datasets, which have every station's metadata in them
+    fn ncei(normals: Vec<String>, monthly: Vec<String>) -> Self {
+        Self {
+            normals: RawNormals::Ncei(normals),
+            monthly: RawNormals::Ncei(monthly),
+            stations: HashMap::new(),
+            unlocated: Failures::new(),
+            counties: HashMap::new(),
+        }
+    }
+}
+
+enum RawNormals {
+    /// Readings of each normal (in order, and each month in turn for monthly ones), by state, or
+    /// why the request failed
+    Cdo(Vec<Fetched>),
+    /// CSVs of every station's normals
+    Ncei(Vec<String>),
+}
+
+/// Every station's readings of some normals, where the stations are if the normals say, and why
+/// states have no readings of each normal when something went wrong
+struct Parsed {
+    readings: Readings,
+    stations: HashMap<String, Station>,
+    failures: Vec<Failures>,
+}
+
+/// Fetch weather data from NOAA for every state in `regions`. Intermediate results are stored in
+/// raw_data. The default [`Dataset`] requires a NOAA_TOKEN env var.
+pub struct Noaa {
+    regions: Regions,
+    http: Http,
+    aggregation: Aggregation,
+    dataset: Dataset,
+    station_file: bool,
+}
+
+impl Noaa {
+    pub fn new(regions: Regions, http: Http) -> Self {
+        Self {
+            regions,
+            http,
+            aggregation: Aggregation::default(),
+            dataset: Dataset::default(),
+            station_file: false,
+        }
+    }
+
+    /// Configured by the `NOAA_AGGREGATION` and `NOAA_DATASET` env vars, see
+    /// [`Aggregation::from_env`] and [`Dataset::from_env`], and `NOAA_STATIONS` set to anything
+    /// writes [`STATION_FILE`]
+    pub fn from_env(regions: Regions, http: Http) -> Result<Self> {
+        Ok(Self::new(regions, http)
+            .with_aggregation(Aggregation::from_env()?)
+            .with_dataset(Dataset::from_env()?)
+            .with_station_file(env::var("NOAA_STATIONS").is_ok()))
+    }
+
+    pub fn with_aggregation(mut self, aggregation: Aggregation) -> Self {
+        self.aggregation = aggregation;
+        self
+    }
+
+    pub fn with_dataset(mut self, dataset: Dataset) -> Self {
+        self.dataset = dataset;
+        self
+    }
+
+    /// Also write every station behind each of [`NORMALS`] to [`STATION_FILE`], so they can be
+    /// mapped and each state's value checked
+    pub fn with_station_file(mut self, station_file: bool) -> Self {
+        self.station_file = station_file;
+        self
+    }
+
+    /// The normal's source, with the years, where it came from, and how stations were aggregated
+    fn source(&self, normal: &Normal) -> Source {
+        Source {
+            source: format!("{}.json", normal.file).into(),
+            name: Cow::Borrowed(normal.name),
+            data_type: DataType::Number,
+            source_url: Cow::Borrowed(self.dataset.source_url()),
+            description: format!(
+                "Long-term averages of {} from {}. {}",
+                normal.what,
+                self.dataset.years(),
+                self.aggregation.description()
+            )
+            .into(),
+            units: Some(Cow::Borrowed(normal.units)),
+            min: normal.min,
+            max: normal.max,
+            sort: Sort::Descending,
+            points: if self.station_file {
+                Some(Cow::Borrowed(STATION_FILE))
+            } else {
+                None
+            },
+            ..Source::BASE
+        }
+    }
+
+    /// The monthly normal's source, which is the average month
+    fn monthly_source(&self, normal: &Normal) -> Source {
+        let source = self.source(normal);
+        let description = format!(
+            "{} This is the average month, {} has every month.",
+            source.description, CLIMATE_PROFILE
+        );
+        Source {
+            description: description.into(),
+            months: Some(Cow::Borrowed(CLIMATE_PROFILE)),
+            points: None,
+            ..source
+        }
+    }
+
+    /// Every station's readings of `normals`, for each month if `monthly`. A state whose requests
+    /// failed has no readings, and the reason is kept.
+    fn readings(&self, raw: RawNormals, normals: &[Normal], monthly: bool) -> Result<Parsed> {
+        match raw {
+            RawNormals::Cdo(fetched) => {
+                let periods = if monthly { 12 } else { 1 };
+                let mut readings: Readings = Vec::with_capacity(normals.len());
+                let mut failures = Vec::with_capacity(normals.len());
+                let mut fetched = fetched.into_iter();
+                for _ in normals {
+                    let mut failed = Failures::new();
+                    let mut normal = Vec::with_capacity(periods);
+                    for states in fetched.by_ref().take(periods) {
+                        let mut period = HashMap::with_capacity(states.len());
+                        for (state, result) in states {
+                            match result {
+                                Ok(parsed) => {
+                                    period.insert(state, parsed);
+                                }
+                                Err(reason) => {
+                                    period.insert(state, Vec::new());
+                                    failed.insert(state, reason);
+                                }
+                            }
+                        }
+                        normal.push(period);
+                    }
+                    readings.push(normal);
+                    failures.push(failed);
+                }
+                Ok(Parsed {
+                    readings,
+                    stations: HashMap::new(),
+                    failures,
+                })
+            }
+            RawNormals::Ncei(bodies) => {
+                let (readings, stations) = ncei::parse(&bodies, normals, monthly, self.regions)?;
+                Ok(Parsed {
+                    readings,
+                    stations,
+                    failures: vec![Failures::new(); normals.len()],
+                })
+            }
+        }
+    }
+
+    /// Why each state in `regions` without a value from `periods` (the year, or every month) has
+    /// none: the reason in `failures`, or that NOAA has no normals for it in any period or in some
+    /// months. States with stations but no value (e.g. none of them are located) aren't included.
+    fn missing(
+        &self,
+        periods: &[HashMap<Code, Vec<Reading>>],
+        failures: &Failures,
+    ) -> Vec<(Code, String)> {
+        self.regions
+            .iter()
+            .filter_map(|state| {
+                if let Some(reason) = failures.get(&state) {
+                    return Some((state, reason.clone()));
+                }
+                let without: Vec<String> = periods
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, readings)| readings.get(&state).map_or(true, Vec::is_empty))
+                    .map(|(month, _)| (month + 1).to_string())
+                    .collect();
+                let reason = match without.len() {
+                    0 => return None,
+                    all if all == periods.len() => "NOAA has no normals for it".to_owned(),
+                    _ => format!(
+                        "NOAA has no normals for it in months {}",
+                        without.join(", ")
+                    ),
+                };
+                Some((state, reason))
+            })
+            .collect()
+    }
+
+    /// Fetch each of [`NORMALS`] and [`MONTHLY_NORMALS`] for every state from Climate Data
+    /// Online, and every station's metadata
+    async fn fetch_cdo(&self) -> Result<Raw> {
+        let token = env::var("NOAA_TOKEN")?;
+        let states: HashMap<ID, Code> = get_states(&token, &self.http)
+            .await?
+            .into_iter()
+            .filter(|(_id, state)| self.regions.contains(*state))
+            .collect();
+        let mut bodies = Vec::with_capacity(NORMALS.len());
+        for normal in &NORMALS {
+            bodies
+                .push(get_readings(&token, DATA_URL, normal.data_type, &states, &self.http).await?);
+        }
+        let mut monthly = Vec::with_capacity(MONTHLY_NORMALS.len() * 12);
+        for normal in &MONTHLY_NORMALS {
+            for month in 1..=12 {
+                let url = format!(
+                    "{}&startdate=2010-{:02}-01&enddate=2010-{:02}-01",
+                    MONTHLY_URL, month, month
+                );
+                monthly
+                    .push(get_readings(&token, &url, normal.data_type, &states, &self.http).await?);
+            }
+        }
+        let (stations, unlocated) = get_stations(&token, &states, &self.http).await?;
+        Ok(Raw {
+            normals: RawNormals::Cdo(bodies),
+            monthly: RawNormals::Cdo(monthly),
+            stations,
+            unlocated,
+            counties: HashMap::new(),
+        })
+    }
+}
+
+#[async_trait]
+impl Collector for Noaa {
+    type Raw = Raw;
+
+    fn name(&self) -> &'static str {
+        "noaa"
+    }
+
+    fn required_env(&self) -> &'static [&'static str] {
+        match self.dataset {
+            Dataset::Cdo => &["NOAA_TOKEN"],
+            Dataset::Access | Dataset::Csv(_) => &[],
+        }
+    }
+
+    fn sources(&self) -> Vec<Source> {
+        let values = NORMALS.iter().map(|normal| self.source(normal));
+        let monthly = MONTHLY_NORMALS
+            .iter()
+            .map(|normal| self.monthly_source(normal));
+        values.chain(monthly).collect()
+    }
+
+    async fn fetch(&self) -> Result<Self::Raw> {
+        let normals = async {
+            match &self.dataset {
+                Dataset::Cdo => self.fetch_cdo().await,
+                Dataset::Access => {
+                    let (normals, monthly) = try_join(
+                        ncei::fetch_access(
+                            &self.http,
+                            "normals-annualseasonal-1991-2020",
+                            &NORMALS,
+                        ),
+                        ncei::fetch_access(
+                            &self.http,
+                            "normals-monthly-1991-2020",
+                            &MONTHLY_NORMALS,
+                        ),
+                    )
+                    .await?;
+                    Ok(Raw::ncei(vec![normals], vec![monthly]))
+                }
+                Dataset::Csv(dir) => Ok(Raw::ncei(
+                    ncei::read_dir(&dir.join("annualseasonal"))?,
+                    ncei::read_dir(&dir.join("monthly"))?,
+                )),
+            }
+        };
+        let counties = async {
+            if self.aggregation == Aggregation::Population {
+                get_counties(&self.http).await
+            } else {
+                Ok(HashMap::new())
+            }
+        };
+        let (raw, counties) = try_join(normals, counties).await?;
+        Ok(Raw { counties, ..raw })
+    }
+
+    fn parse(&self, raw: Self::Raw) -> Result<Vec<Metric>> {
+        let annual = self.readings(raw.normals, &NORMALS, false)?;
+        let monthly = self.readings(raw.monthly, &MONTHLY_NORMALS, true)?;
+        let mut stations = annual.stations;
+        stations.extend(raw.stations);
+        stations.extend(monthly.stations);
+        let counties = raw.counties;
+        let unlocated = raw.unlocated;
+        let aggregate = |state: &Code, readings: &[Reading]| {
+            let counties = counties.get(state).map_or(&[][..], Vec::as_slice);
+            self.aggregation.aggregate(readings, &stations, counties)
+        };
+        // Weighting by population needs to know where every station is
+        let value_failures = |failures: &Failures| {
+            let mut failures = failures.clone();
+            if self.aggregation == Aggregation::Population {
+                for (state, reason) in &unlocated {
+                    failures.entry(*state).or_insert_with(|| reason.clone());
+                }
+            }
+            failures
+        };
+
+        let mut values = Vec::with_capacity(NORMALS.len() + MONTHLY_NORMALS.len());
+        for ((normal, mut readings), failures) in
+            NORMALS.iter().zip(annual.readings).zip(&annual.failures)
+        {
+            let readings = readings.remove(0);
+            let mut value = Vec::with_capacity(readings.len());
+            let mut samples = Vec::with_capacity(readings.len());
+            for (state, readings) in &readings {
+                let aggregated = aggregate(state, readings);
+                value.push((*state, aggregated));
+                // Only behind a value, so a failed state doesn't look like it has no stations
+                if aggregated.is_some() {
+                    samples.push((*state, readings.len()));
+                }
+            }
+            let metric = Metric::new(self.source(normal), value)
+                .with_samples(samples)
+                .with_missing(
+                    self.missing(std::slice::from_ref(&readings), &value_failures(failures)),
+                );
+            values.push(if self.station_file {
+                metric.with_points(STATION_FILE, points(&readings, &stations))
+            } else {
+                metric
+            });
+        }
+
+        for ((normal, readings), failures) in MONTHLY_NORMALS
+            .iter()
+            .zip(monthly.readings)
+            .zip(&monthly.failures)
+        {
+            let mut months = Months::new();
+            let mut samples: HashMap<Code, HashSet<&str>> = HashMap::new();
+            for (month, readings) in readings.iter().enumerate() {
+                for (state, readings) in readings {
+                    months.entry(*state).or_insert([None; 12])[month] = aggregate(state, readings);
+                    let ids = samples.entry(*state).or_default();
+                    ids.extend(readings.iter().map(|reading| reading.station.as_str()));
+                }
+            }
+            let samples = samples
+                .into_iter()
+                .filter(|(state, _)| months[state].iter().any(Option::is_some))
+                .map(|(state, stations)| (state, stations.len()))
+                .collect::<Vec<_>>();
+            values.push(
+                Metric::with_months(self.monthly_source(normal), CLIMATE_PROFILE, months)
+                    .with_samples(samples)
+                    .with_missing(self.missing(&readings, &value_failures(failures))),
+            );
+        }
+        Ok(values)
+    }
+}
@@ -0,0 +1,151 @@
+//! The 1991–2020 normals, which aren't in Climate Data Online. NCEI's data access service and the
+//! bulk by-station files both have the same CSV columns: the station, its name (ending in the
+//! state, e.g. "MOBILE, AL US"), its location, the month for monthly normals, then one column per
+//! data type.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+
+use collector::Http;
+use states::{Regions, State};
+
+use crate::aggregate::{Location, Reading, Station};
+use crate::normals::Normal;
+use crate::Readings;
+
+const ACCESS_URL: &str = "https://www.ncei.noaa.gov/access/services/data/v1";
+const ACCESS_PARAMS: &str = "&boundingBox=90,-180,-90,180&includeStationName=true&includeStationLocation=1&units=standard&format=csv";
+
+/// Every station's `normals` from the data access service's `dataset`, e.g.
+/// "normals-monthly-1991-2020", as one CSV
+pub(crate) async fn fetch_access(http: &Http, dataset: &str, normals: &[Normal]) -> Result<String> {
+    let data_types: Vec<&str> = normals.iter().map(|normal| normal.data_type).collect();
+    let url = format!(
+        "{}?dataset={}{}&dataTypes={}",
+        ACCESS_URL,
+        dataset,
+        ACCESS_PARAMS,
+        data_types.join(",")
+    );
+    http.get_text(&url, &[])
+        .await
+        .with_context(|| format!("Could not fetch the {} normals", dataset))
+}
+
+/// Every CSV file in `dir`, one per station
+pub(crate) fn read_dir(dir: &Path) -> Result<Vec<String>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| {
+        format!(
+            "Could not read {}, download and extract the 1991-2020 normals there",
+            dir.display()
+        )
+    })? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|extension| extension == "csv") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    paths
+        .iter()
+        .map(|path| {
+            fs::read_to_string(path).with_context(|| format!("Could not read {}", path.display()))
+        })
+        .collect()
+}
+
+/// Readings of `normals` for every state in `regions`, for each month if `monthly`, along with
+/// every station's metadata
+pub(crate) fn parse(
+    bodies: &[String],
+    normals: &[Normal],
+    monthly: bool,
+    regions: Regions,
+) -> Result<(Readings, HashMap<String, Station>)> {
+    let empty: HashMap<State, Vec<Reading>> =
+        regions.iter().map(|state| (state, Vec::new())).collect();
+    let periods = if monthly { 12 } else { 1 };
+    let mut readings = vec![vec![empty; periods]; normals.len()];
+    let mut stations = HashMap::new();
+    for body in bodies {
+        let mut reader = csv::Reader::from_reader(body.as_bytes());
+        let headers = reader.headers()?.clone();
+        let column = |name: &str| {
+            headers
+                .iter()
+                .position(|header| header == name)
+                .ok_or_else(|| anyhow!("Normals are missing the {} column", name))
+        };
+        let (station, name) = (column("STATION")?, column("NAME")?);
+        let (latitude, longitude) = (column("LATITUDE")?, column("LONGITUDE")?);
+        let elevation = column("ELEVATION").ok();
+        let date = if monthly { Some(column("DATE")?) } else { None };
+        // Not every station has every data type
+        let data_types: Vec<Option<usize>> = normals
+            .iter()
+            .map(|normal| column(normal.data_type).ok())
+            .collect();
+        for record in reader.records() {
+            let record = record?;
+            let state = match state(&record[name]) {
+                Some(state) if regions.contains(state) => state,
+                _ => continue,
+            };
+            let period = match date {
+                Some(date) => match record[date].trim().parse::<usize>() {
+                    Ok(month) if (1..=12).contains(&month) => month - 1,
+                    _ => return Err(anyhow!("Invalid month {} in normals", &record[date])),
+                },
+                None => 0,
+            };
+            let id = record[station].to_owned();
+            if let (Ok(latitude), Ok(longitude)) = (
+                record[latitude].trim().parse(),
+                record[longitude].trim().parse(),
+            ) {
+                let station = Station {
+                    name: record[name].to_owned(),
+                    state,
+                    location: Location {
+                        latitude,
+                        longitude,
+                    },
+                    elevation: elevation.and_then(|column| record[column].trim().parse().ok()),
+                };
+                stations.insert(id.clone(), station);
+            }
+            for (readings, column) in readings.iter_mut().zip(&data_types) {
+                if let Some(value) = column.and_then(|column| value(&record[column])) {
+                    readings[period].entry(state).or_default().push(Reading {
+                        station: id.clone(),
+                        value,
+                    });
+                }
+            }
+        }
+    }
+    Ok((readings, stations))
+}
+
+/// The state at the end of a station name like "MOBILE, AL US"
+fn state(name: &str) -> Option<State> {
+    let code = name.rsplit(", ").next()?.strip_suffix(" US")?;
+    State::from_code(code.trim())
+}
+
+/// Trace amounts are -7777, which count as 0. Other negative flags from -5555 to -9999 mean there
+/// is no value.
+fn value(field: &str) -> Option<f64> {
+    let value: f64 = field.trim().parse().ok()?;
+    if value == -7777.0 {
+        Some(0.0)
+    } else if [-9999.0, -8888.0, -6666.0, -5555.0].contains(&value) {
+        None
+    } else {
+        Some(value)
+    }
+}
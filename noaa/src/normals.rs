@@ -0,0 +1,145 @@
+//! The NOAA data types collected as climate normals, annual and monthly.
+
+/// A NOAA data type, e.g. "ANN-TAVG-NORMAL", and the metric it produces. The rest of the metric's
+/// source depends on the [`crate::Dataset`].
+pub(crate) struct Normal {
+    pub(crate) data_type: &'static str,
+    /// The file in `generated`, without ".json"
+    pub(crate) file: &'static str,
+    pub(crate) name: &'static str,
+    pub(crate) units: &'static str,
+    /// Finishes "Long-term averages of ..." for the description
+    pub(crate) what: &'static str,
+    pub(crate) min: Option<f64>,
+    pub(crate) max: Option<f64>,
+}
+
+/// Days in a year, the most any count of days can be
+const DAYS: Option<f64> = Some(366.0);
+
+pub(crate) static NORMALS: [Normal; 10] = [
+    Normal {
+        data_type: "ANN-TAVG-NORMAL",
+        file: "annual_temp",
+        name: "Average Temperature",
+        units: "ºF",
+        what: "annual average temperature",
+        min: None,
+        max: None,
+    },
+    Normal {
+        data_type: "MAM-TAVG-NORMAL",
+        file: "spring_temp",
+        name: "Average Spring Temperature",
+        units: "ºF",
+        what: "spring average temperature",
+        min: None,
+        max: None,
+    },
+    Normal {
+        data_type: "JJA-TAVG-NORMAL",
+        file: "summer_temp",
+        name: "Average Summer Temperature",
+        units: "ºF",
+        what: "summer average temperature",
+        min: None,
+        max: None,
+    },
+    Normal {
+        data_type: "SON-TAVG-NORMAL",
+        file: "autumn_temp",
+        name: "Average Autumn Temperature",
+        units: "ºF",
+        what: "autumn average temperature",
+        min: None,
+        max: None,
+    },
+    Normal {
+        data_type: "DJF-TAVG-NORMAL",
+        file: "winter_temp",
+        name: "Average Winter Temperature",
+        units: "ºF",
+        what: "winter average temperature",
+        min: None,
+        max: None,
+    },
+    Normal {
+        data_type: "ANN-PRCP-NORMAL",
+        file: "annual_precipitation",
+        name: "Annual Precipitation",
+        units: "in",
+        what: "total annual precipitation (rain and melted snow)",
+        min: Some(0.0),
+        max: None,
+    },
+    Normal {
+        data_type: "ANN-SNOW-NORMAL",
+        file: "annual_snowfall",
+        name: "Annual Snowfall",
+        units: "in",
+        what: "total annual snowfall",
+        min: Some(0.0),
+        max: None,
+    },
+    Normal {
+        data_type: "ANN-PRCP-AVGNDS-GE010HI",
+        file: "rainy_days",
+        name: "Rainy Days",
+        units: "days",
+        what: "the number of days a year with at least 0.1 inches of precipitation",
+        min: Some(0.0),
+        max: DAYS,
+    },
+    Normal {
+        data_type: "ANN-TMAX-AVGNDS-GRTH090",
+        file: "hot_days",
+        name: "Hot Days",
+        units: "days",
+        what: "the number of days a year with a high of at least 90ºF",
+        min: Some(0.0),
+        max: DAYS,
+    },
+    Normal {
+        data_type: "ANN-TMIN-AVGNDS-LSTH032",
+        file: "freezing_days",
+        name: "Freezing Days",
+        units: "days",
+        what: "the number of days a year with a low of 32ºF or below",
+        min: Some(0.0),
+        max: DAYS,
+    },
+];
+
+/// Every state's values for each month of these are in this file, see [`metrics::Months`]
+pub(crate) const CLIMATE_PROFILE: &str = "climate_profile.json";
+
+/// Normals with a value for each month. The metric itself is the average month.
+pub(crate) static MONTHLY_NORMALS: [Normal; 3] = [
+    Normal {
+        data_type: "MLY-TMAX-NORMAL",
+        file: "average_high",
+        name: "Average High",
+        units: "ºF",
+        what: "the daily high",
+        min: None,
+        max: None,
+    },
+    Normal {
+        data_type: "MLY-TMIN-NORMAL",
+        file: "average_low",
+        name: "Average Low",
+        units: "ºF",
+        what: "the daily low",
+        min: None,
+        max: None,
+    },
+    Normal {
+        data_type: "MLY-PRCP-NORMAL",
+        file: "monthly_precipitation",
+        name: "Monthly Precipitation",
+        units: "in",
+        what: "total precipitation (rain and melted snow) each month",
+        min: Some(0.0),
+        max: None,
+    },
+];
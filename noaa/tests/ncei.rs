@@ -0,0 +1,271 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use collector::testing::collect;
+use collector::{save_fixture, AnyCollector, Collector, Http, Mode};
+use metrics::Metric;
+use noaa::{Aggregation, Dataset, Noaa};
+use states::{Regions, State};
+
+const ACCESS_URL: &str = "https://www.ncei.noaa.gov/access/services/data/v1?dataset=normals-annualseasonal-1991-2020&boundingBox=90,-180,-90,180&includeStationName=true&includeStationLocation=1&units=standard&format=csv&dataTypes=ANN-TAVG-NORMAL,MAM-TAVG-NORMAL,JJA-TAVG-NORMAL,SON-TAVG-NORMAL,DJF-TAVG-NORMAL,ANN-PRCP-NORMAL,ANN-SNOW-NORMAL,ANN-PRCP-AVGNDS-GE010HI,ANN-TMAX-AVGNDS-GRTH090,ANN-TMIN-AVGNDS-LSTH032";
+const MONTHLY_URL: &str = "https://www.ncei.noaa.gov/access/services/data/v1?dataset=normals-monthly-1991-2020&boundingBox=90,-180,-90,180&includeStationName=true&includeStationLocation=1&units=standard&format=csv&dataTypes=MLY-TMAX-NORMAL,MLY-TMIN-NORMAL,MLY-PRCP-NORMAL";
+const COUNTIES_URL: &str =
+    "https://api.census.gov/data/2020/dec/pl?get=P1_001N,INTPTLAT,INTPTLON&for=county:*";
+const HEADER: &str = "\"STATION\",\"DATE\",\"LATITUDE\",\"LONGITUDE\",\"ELEVATION\",\"NAME\",\"ANN-TAVG-NORMAL\",\"ANN-PRCP-NORMAL\",\"ANN-SNOW-NORMAL\"";
+
+/// Two stations in Alabama and one each in Alaska and DC, plus one outside the U.S. Alabama's
+/// second station had only a trace of snow and is missing its precipitation.
+const ROWS: [&str; 5] = [
+    "\"USW00013894\",\"01-01\",\"30.6883\",\"-88.2456\",\"65.2\",\"MOBILE RGNL AP, AL US\",\"   68.0\",\"   65.2\",\"    0.3\"",
+    "\"USC00010160\",\"01-01\",\"34.5\",\"-86.0\",\"200.0\",\"ALBERTVILLE 2 SE, AL US\",\"   62.0\",\"-9999\",\"-7777\"",
+    "\"USW00026451\",\"01-01\",\"61.1689\",\"-150.0278\",\"36.3\",\"ANCHORAGE INTL AP, AK US\",\"   38.0\",\"   16.6\",\"   75.5\"",
+    "\"USW00013743\",\"01-01\",\"38.8483\",\"-77.0342\",\"3.0\",\"WASHINGTON REAGAN AP, DC US\",\"   59.0\",\"   41.8\",\"   13.7\"",
+    "\"CA006158355\",\"01-01\",\"43.6772\",\"-79.6306\",\"173.4\",\"TORONTO INTL A, ON CA\",\"   48.0\",\"   31.0\",\"   47.0\"",
+];
+
+const MONTHLY_HEADER: &str = "\"STATION\",\"DATE\",\"LATITUDE\",\"LONGITUDE\",\"ELEVATION\",\"NAME\",\"MLY-TMAX-NORMAL\",\"MLY-TMIN-NORMAL\",\"MLY-PRCP-NORMAL\"";
+
+/// Every month of Mobile, which is a degree warmer each month, with a trace of rain in October
+fn monthly_rows() -> Vec<String> {
+    (1..=12)
+        .map(|month| {
+            format!(
+                "\"USW00013894\",\"{:02}\",\"30.6883\",\"-88.2456\",\"65.2\",\"MOBILE RGNL AP, AL US\",\"{}\",\"{}\",\"{}\"",
+                month,
+                60 + month,
+                40 + month,
+                if month == 10 { -7777 } else { 5 }
+            )
+        })
+        .collect()
+}
+
+async fn access(name: &str, regions: Regions, aggregation: Aggregation) -> Vec<Metric> {
+    let fixtures = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = fs::remove_dir_all(&fixtures);
+    let mut body = vec![HEADER];
+    body.extend(&ROWS);
+    save_fixture(&fixtures, ACCESS_URL, &body.join("\n"))
+        .await
+        .unwrap();
+    let mut monthly = vec![MONTHLY_HEADER.to_owned()];
+    monthly.extend(monthly_rows());
+    save_fixture(&fixtures, MONTHLY_URL, &monthly.join("\n"))
+        .await
+        .unwrap();
+    // Mobile's county has three times as many people as Albertville's
+    save_fixture(
+        &fixtures,
+        COUNTIES_URL,
+        r#"[["P1_001N","INTPTLAT","INTPTLON","state","county"],
+            ["3000","+30.7","-88.1","01","097"],
+            ["1000","+34.4","-86.1","01","095"]]"#,
+    )
+    .await
+    .unwrap();
+    collect(
+        Noaa::new(regions, Http::new(Mode::Replay(fixtures)))
+            .with_aggregation(aggregation)
+            .with_dataset(Dataset::Access)
+            .with_station_file(true),
+    )
+    .await
+}
+
+/// How many stations are behind `state`'s value
+fn samples(metric: &Metric, state: State) -> Option<usize> {
+    metric.samples()?.get(&state).copied()
+}
+
+#[tokio::test]
+async fn access_reads_every_station_by_state() {
+    let metrics = access(
+        "access_reads_every_station_by_state",
+        Regions::States,
+        Aggregation::Mean,
+    )
+    .await;
+    let annual_temp = &metrics[0];
+    assert_eq!(annual_temp.get(State::Alabama), Some(65.0));
+    assert_eq!(annual_temp.get(State::Alaska), Some(38.0));
+    assert_eq!(annual_temp.get(State::DistrictOfColumbia), None);
+    // Not in the data at all
+    assert_eq!(annual_temp.get(State::Wyoming), None);
+    assert_eq!(samples(annual_temp, State::Alabama), Some(2));
+    assert_eq!(samples(annual_temp, State::Wyoming), None);
+    assert_eq!(
+        annual_temp.source().description,
+        "Long-term averages of annual average temperature from 1991 to 2020. Averaged across \
+         every station in the state."
+    );
+    assert_eq!(
+        annual_temp.source().source_url,
+        "https://www.ncei.noaa.gov/products/land-based-station/us-climate-normals"
+    );
+}
+
+#[tokio::test]
+async fn access_skips_missing_values_and_counts_traces_as_zero() {
+    let metrics = access(
+        "access_skips_missing_values_and_counts_traces_as_zero",
+        Regions::StatesAndDc,
+        Aggregation::Mean,
+    )
+    .await;
+    let (precipitation, snowfall) = (&metrics[5], &metrics[6]);
+    assert_eq!(precipitation.get(State::Alabama), Some(65.2));
+    assert_eq!(snowfall.get(State::Alabama), Some(0.15));
+    assert_eq!(samples(precipitation, State::Alabama), Some(1));
+    assert_eq!(samples(snowfall, State::Alabama), Some(2));
+    // A data type missing from the file has no stations
+    assert_eq!(metrics[1].get(State::Alabama), None);
+    assert_eq!(precipitation.get(State::DistrictOfColumbia), Some(41.8));
+}
+
+#[tokio::test]
+async fn access_weights_by_population_with_station_locations_from_the_file() {
+    let metrics = access(
+        "access_weights_by_population_with_station_locations_from_the_file",
+        Regions::States,
+        Aggregation::Population,
+    )
+    .await;
+    assert_eq!(metrics[0].get(State::Alabama), Some(66.5));
+}
+
+fn station_files(name: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = fs::remove_dir_all(&dir);
+    let (annual, monthly) = (dir.join("annualseasonal"), dir.join("monthly"));
+    fs::create_dir_all(&annual).unwrap();
+    fs::create_dir_all(&monthly).unwrap();
+    for row in &ROWS {
+        let station = &row[1..12];
+        fs::write(
+            annual.join(format!("{}.csv", station)),
+            format!("{}\n{}\n", HEADER, row),
+        )
+        .unwrap();
+    }
+    fs::write(annual.join("readme.txt"), "Not a station").unwrap();
+    fs::write(
+        monthly.join("USW00013894.csv"),
+        format!("{}\n{}\n", MONTHLY_HEADER, monthly_rows().join("\n")),
+    )
+    .unwrap();
+    dir
+}
+
+#[tokio::test]
+async fn csv_reads_a_file_per_station() {
+    let dir = station_files("csv_reads_a_file_per_station");
+    let metrics =
+        collect(Noaa::new(Regions::States, Http::new(Mode::Live)).with_dataset(Dataset::Csv(dir)))
+            .await;
+    assert_eq!(metrics[0].get(State::Alabama), Some(65.0));
+    assert_eq!(metrics[0].get(State::Alaska), Some(38.0));
+    assert_eq!(samples(&metrics[0], State::Alabama), Some(2));
+    assert_eq!(
+        metrics[10].months().unwrap()[&State::Alabama][6],
+        Some(67.0)
+    );
+}
+
+#[tokio::test]
+async fn csv_reports_the_months_a_state_is_missing() {
+    let dir = station_files("csv_reports_the_months_a_state_is_missing");
+    let first_half = monthly_rows()[..6].join("\n");
+    fs::write(
+        dir.join("monthly/USW00013894.csv"),
+        format!("{}\n{}\n", MONTHLY_HEADER, first_half),
+    )
+    .unwrap();
+    let metrics =
+        collect(Noaa::new(Regions::States, Http::new(Mode::Live)).with_dataset(Dataset::Csv(dir)))
+            .await;
+    let high = &metrics[10];
+    assert_eq!(high.months().unwrap()[&State::Alabama][5], Some(66.0));
+    assert_eq!(high.get(State::Alabama), None);
+    assert_eq!(
+        high.missing()[&State::Alabama],
+        "NOAA has no normals for it in months 7, 8, 9, 10, 11, 12"
+    );
+    assert_eq!(high.missing()[&State::Alaska], "NOAA has no normals for it");
+    // Every annual normal has a station
+    assert_eq!(metrics[0].missing().get(&State::Alabama), None);
+}
+
+#[tokio::test]
+async fn csv_needs_the_files() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("csv_needs_the_files");
+    let _ = fs::remove_dir_all(&dir);
+    let error = Noaa::new(Regions::States, Http::new(Mode::Live))
+        .with_dataset(Dataset::Csv(dir.clone()))
+        .collect()
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "Could not read {}, download and extract the 1991-2020 normals there",
+            dir.join("annualseasonal").display()
+        )
+    );
+}
+
+#[tokio::test]
+async fn access_has_every_station() {
+    let metrics = access(
+        "access_has_every_station",
+        Regions::States,
+        Aggregation::Mean,
+    )
+    .await;
+    let points = metrics[0].points().unwrap();
+    let ids: Vec<&str> = points.iter().map(|point| &*point.id).collect();
+    assert_eq!(ids, ["USC00010160", "USW00013894", "USW00026451"]);
+    assert_eq!(points[1].name, "MOBILE RGNL AP, AL US");
+    assert_eq!(points[1].elevation, Some(65.2));
+    assert_eq!(points[1].value, 68.0);
+    // Albertville is missing precipitation
+    assert_eq!(metrics[5].points().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn access_reads_every_month() {
+    let metrics = access(
+        "access_reads_every_month",
+        Regions::States,
+        Aggregation::Mean,
+    )
+    .await;
+    let (high, low, precipitation) = (&metrics[10], &metrics[11], &metrics[12]);
+    assert_eq!(high.months().unwrap()[&State::Alabama][0], Some(61.0));
+    assert_eq!(low.months().unwrap()[&State::Alabama][11], Some(52.0));
+    assert_eq!(
+        precipitation.months().unwrap()[&State::Alabama][9],
+        Some(0.0)
+    );
+    assert_eq!(high.get(State::Alabama), Some(66.5));
+    assert_eq!(high.months().unwrap()[&State::Alaska], [None; 12]);
+    assert_eq!(high.get(State::Alaska), None);
+    assert_eq!(samples(high, State::Alabama), Some(1));
+    assert_eq!(samples(high, State::Alaska), None);
+}
+
+#[test]
+fn only_cdo_needs_a_token() {
+    let noaa = |dataset| Noaa::new(Regions::States, Http::new(Mode::Live)).with_dataset(dataset);
+    assert_eq!(Collector::required_env(&noaa(Dataset::Cdo)), ["NOAA_TOKEN"]);
+    assert!(Collector::required_env(&noaa(Dataset::Access)).is_empty());
+    assert!(Collector::required_env(&noaa(Dataset::Csv(PathBuf::new()))).is_empty());
+}
+
+#[test]
+fn dataset_is_parsed() {
+    assert_eq!("Access".parse(), Ok(Dataset::Access));
+    assert_eq!(
+        "ghcn".parse::<Dataset>().unwrap_err().to_string(),
+        "\"ghcn\" is not a valid NOAA dataset, expected \"cdo\", \"access\", or \"csv\""
+    );
+}
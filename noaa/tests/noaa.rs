@@ -0,0 +1,512 @@
+use std::env;
+use std::path::{Path, PathBuf};
+
+use collector::testing::{collect, written};
+use collector::{fixture_path, save_fixture, AnyCollector, Http, Mode};
+use metrics::{validate, Metric};
+use noaa::{Aggregation, Noaa, STATION_FILE};
+use states::{Regions, State};
+
+const STATES_URL: &str = "https://www.ncdc.noaa.gov/cdo-web/api/v2/locations?datasetid=NORMAL_ANN&locationcategoryid=ST&limit=1000";
+const DATA_URL: &str = "https://www.ncdc.noaa.gov/cdo-web/api/v2/data?datasetid=NORMAL_ANN&startdate=2000-01-01&enddate=2010-01-01&units=standard&limit=1000";
+const MONTHLY_URL: &str =
+    "https://www.ncdc.noaa.gov/cdo-web/api/v2/data?datasetid=NORMAL_MLY&units=standard&limit=1000";
+const STATIONS_URL: &str =
+    "https://www.ncdc.noaa.gov/cdo-web/api/v2/stations?datasetid=NORMAL_ANN&limit=1000";
+const COUNTIES_URL: &str =
+    "https://api.census.gov/data/2020/dec/pl?get=P1_001N,INTPTLAT,INTPTLON&for=county:*";
+const DATA_TYPES: [&str; 10] = [
+    "ANN-TAVG-NORMAL",
+    "MAM-TAVG-NORMAL",
+    "JJA-TAVG-NORMAL",
+    "SON-TAVG-NORMAL",
+    "DJF-TAVG-NORMAL",
+    "ANN-PRCP-NORMAL",
+    "ANN-SNOW-NORMAL",
+    "ANN-PRCP-AVGNDS-GE010HI",
+    "ANN-TMAX-AVGNDS-GRTH090",
+    "ANN-TMIN-AVGNDS-LSTH032",
+];
+
+const MONTHLY_TYPES: [&str; 3] = ["MLY-TMAX-NORMAL", "MLY-TMIN-NORMAL", "MLY-PRCP-NORMAL"];
+
+/// Value, latitude, longitude and the population of the county around a station
+type Station = (f64, f64, f64, u32);
+
+/// Two states, DC and a row which isn't a state at all. Every data type has the same stations:
+/// two reading 50 and 60 in Alabama, three reading 5, 20 and 20 in Alaska and two reading 40 and
+/// 50 in DC. Monthly data types are the same in January, one more each month after that. Each
+/// station has a county of people near it, Alabama's 3000 and 1000, Alaska's 300,
+/// 100 and 100, and DC's 700 and 0.
+async fn fixtures(name: &str) -> PathBuf {
+    let fixtures = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_dir_all(&fixtures);
+    save_fixture(
+        &fixtures,
+        &format!("{}&offset=1", STATES_URL),
+        r#"{"metadata":{"resultset":{"offset":1,"count":4,"limit":1000}},"results":[
+            {"mindate":"2010-01-01","maxdate":"2010-01-01","name":"Alabama","datacoverage":1,"id":"FIPS:01"},
+            {"mindate":"2010-01-01","maxdate":"2010-01-01","name":"Alaska","datacoverage":1,"id":"FIPS:02"},
+            {"mindate":"2010-01-01","maxdate":"2010-01-01","name":"District of Columbia","datacoverage":1,"id":"FIPS:11"},
+            {"mindate":"2010-01-01","maxdate":"2010-01-01","name":"Pacific Islands","datacoverage":1,"id":"FIPS:99"}
+        ]}"#,
+    )
+    .await
+    .unwrap();
+    let states: [(&str, &str, &[Station]); 3] = [
+        (
+            "FIPS:01",
+            "01",
+            &[(50.0, 34.0, -86.0, 3000), (60.0, 31.0, -88.0, 1000)],
+        ),
+        (
+            "FIPS:02",
+            "02",
+            &[
+                (5.0, 61.2, -149.9, 300),
+                (20.0, 64.8, -147.7, 100),
+                (20.0, 58.3, -134.4, 100),
+            ],
+        ),
+        (
+            "FIPS:11",
+            "11",
+            &[(40.0, 38.9, -77.0, 700), (50.0, 38.95, -77.1, 0)],
+        ),
+    ];
+    let mut counties = vec![r#"["P1_001N","INTPTLAT","INTPTLON","state","county"]"#.to_owned()];
+    for (id, fips, stations) in &states {
+        let station_id = |index: usize| format!("GHCND:{}{}", fips, index);
+        for data_type in &DATA_TYPES {
+            let results: Vec<String> = stations
+                .iter()
+                .enumerate()
+                .map(|(index, (value, ..))| {
+                    format!(
+                        r#"{{"date":"2010-01-01T00:00:00","datatype":"{}","station":"{}","attributes":"S","value":{}}}"#,
+                        data_type,
+                        station_id(index),
+                        value
+                    )
+                })
+                .collect();
+            let body = format!(r#"{{"results":[{}]}}"#, results.join(","));
+            let url = format!(
+                "{}&locationid={}&datatypeid={}&offset=1",
+                DATA_URL, id, data_type
+            );
+            save_fixture(&fixtures, &url, &body).await.unwrap();
+        }
+        for data_type in &MONTHLY_TYPES {
+            for month in 1..=12 {
+                let results: Vec<String> = stations
+                    .iter()
+                    .enumerate()
+                    .map(|(index, (value, ..))| {
+                        format!(
+                            r#"{{"date":"2010-{:02}-01T00:00:00","datatype":"{}","station":"{}","attributes":"S","value":{}}}"#,
+                            month,
+                            data_type,
+                            station_id(index),
+                            value + month as f64 - 1.0
+                        )
+                    })
+                    .collect();
+                let body = format!(r#"{{"results":[{}]}}"#, results.join(","));
+                let url = format!(
+                    "{}&startdate=2010-{:02}-01&enddate=2010-{:02}-01&locationid={}&datatypeid={}&offset=1",
+                    MONTHLY_URL, month, month, id, data_type
+                );
+                save_fixture(&fixtures, &url, &body).await.unwrap();
+            }
+        }
+        let results: Vec<String> = stations
+            .iter()
+            .enumerate()
+            .map(|(index, (_, latitude, longitude, population))| {
+                counties.push(format!(
+                    r#"["{}","+{}","{}","{}","{:03}"]"#,
+                    population,
+                    latitude,
+                    longitude,
+                    fips,
+                    index + 1
+                ));
+                format!(
+                    r#"{{"id":"{}","name":"STATION","latitude":{},"longitude":{},"elevation":100}}"#,
+                    station_id(index),
+                    latitude,
+                    longitude
+                )
+            })
+            .collect();
+        let body = format!(r#"{{"results":[{}]}}"#, results.join(","));
+        let url = format!("{}&locationid={}&offset=1", STATIONS_URL, id);
+        save_fixture(&fixtures, &url, &body).await.unwrap();
+    }
+    // Not a state, so skipped
+    counties.push(r#"["5000","+13.4","144.7","66","010"]"#.to_owned());
+    save_fixture(
+        &fixtures,
+        COUNTIES_URL,
+        &format!("[{}]", counties.join(",")),
+    )
+    .await
+    .unwrap();
+    fixtures
+}
+
+/// Everything collected from the [`fixtures`] for `name`
+async fn replay(name: &str, regions: Regions, aggregation: Aggregation) -> Vec<Metric> {
+    env::set_var("NOAA_TOKEN", "test");
+    let http = Http::new(Mode::Replay(fixtures(name).await));
+    collect(Noaa::new(regions, http).with_aggregation(aggregation)).await
+}
+
+/// How many stations are behind `state`'s value
+fn samples(metric: &Metric, state: State) -> Option<usize> {
+    metric.samples()?.get(&state).copied()
+}
+
+#[tokio::test]
+async fn averages_stations_for_every_data_type() {
+    let metrics = replay(
+        "averages_stations_for_every_data_type",
+        Regions::States,
+        Aggregation::Mean,
+    )
+    .await;
+    let files: Vec<&str> = metrics
+        .iter()
+        .map(|metric| &*metric.source().source)
+        .collect();
+    assert_eq!(
+        files,
+        [
+            "annual_temp.json",
+            "spring_temp.json",
+            "summer_temp.json",
+            "autumn_temp.json",
+            "winter_temp.json",
+            "annual_precipitation.json",
+            "annual_snowfall.json",
+            "rainy_days.json",
+            "hot_days.json",
+            "freezing_days.json",
+            "average_high.json",
+            "average_low.json",
+            "monthly_precipitation.json",
+        ]
+    );
+    for metric in &metrics[..10] {
+        assert_eq!(metric.get(State::Alabama), Some(55.0));
+        assert_eq!(metric.get(State::Alaska), Some(15.0));
+        assert_eq!(metric.get(State::DistrictOfColumbia), None);
+    }
+    for metric in &metrics {
+        assert_eq!(samples(metric, State::Alabama), Some(2));
+        assert_eq!(samples(metric, State::Alaska), Some(3));
+    }
+    assert!(metrics[0]
+        .source()
+        .description
+        .ends_with("Averaged across every station in the state."));
+}
+
+#[tokio::test]
+async fn median_takes_the_middle_station() {
+    let metrics = replay(
+        "median_takes_the_middle_station",
+        Regions::States,
+        Aggregation::Median,
+    )
+    .await;
+    assert_eq!(metrics[0].get(State::Alabama), Some(55.0));
+    assert_eq!(metrics[0].get(State::Alaska), Some(20.0));
+}
+
+#[tokio::test]
+async fn population_weights_stations_by_nearby_counties() {
+    let metrics = replay(
+        "population_weights_stations_by_nearby_counties",
+        Regions::StatesAndDc,
+        Aggregation::Population,
+    )
+    .await;
+    assert_eq!(metrics[0].get(State::Alabama), Some(52.5));
+    assert_eq!(metrics[0].get(State::Alaska), Some(11.0));
+    assert_eq!(metrics[0].get(State::DistrictOfColumbia), Some(40.0));
+    // Every station still counts, even one with nobody near it
+    assert_eq!(samples(&metrics[0], State::DistrictOfColumbia), Some(2));
+}
+
+#[tokio::test]
+async fn dc_follows_regions() {
+    let metrics = replay(
+        "dc_follows_regions",
+        Regions::StatesAndDc,
+        Aggregation::Mean,
+    )
+    .await;
+    assert_eq!(metrics[0].get(State::DistrictOfColumbia), Some(45.0));
+}
+
+#[test]
+fn aggregation_is_parsed() {
+    assert_eq!("Median".parse(), Ok(Aggregation::Median));
+    assert_eq!(" population ".parse(), Ok(Aggregation::Population));
+    assert_eq!(
+        "mode".parse::<Aggregation>().unwrap_err().to_string(),
+        "\"mode\" is not a valid NOAA aggregation, expected \"mean\", \"median\", or \"population\""
+    );
+}
+
+#[tokio::test]
+async fn normals_are_described_by_their_data_type() {
+    let metrics = replay(
+        "normals_are_described_by_their_data_type",
+        Regions::States,
+        Aggregation::Mean,
+    )
+    .await;
+    let hot_days = metrics[8].source();
+    assert_eq!(hot_days.source, "hot_days.json");
+    assert_eq!(hot_days.units.as_deref(), Some("days"));
+    assert_eq!((hot_days.min, hot_days.max), (Some(0.0), Some(366.0)));
+    assert_eq!(
+        hot_days.description,
+        "Long-term averages of the number of days a year with a high of at least 90ºF from 1981 to \
+         2010. Averaged across every station in the state."
+    );
+}
+
+#[tokio::test]
+async fn monthly_normals_are_in_the_climate_profile() {
+    let metrics = replay(
+        "monthly_normals_are_in_the_climate_profile",
+        Regions::States,
+        Aggregation::Median,
+    )
+    .await;
+    let high = &metrics[10];
+    assert_eq!(high.source().source, "average_high.json");
+    assert_eq!(
+        high.source().months.as_deref(),
+        Some("climate_profile.json")
+    );
+    let months = high.months().unwrap();
+    assert_eq!(months[&State::Alabama][0], Some(55.0));
+    assert_eq!(months[&State::Alaska][6], Some(26.0));
+    // The average month
+    assert_eq!(high.get(State::Alabama), Some(60.5));
+    assert_eq!(
+        metrics[12].source().description,
+        "Long-term averages of total precipitation (rain and melted snow) each month from 1981 to \
+         2010. The median of every station in the state. This is the average month, \
+         climate_profile.json has every month."
+    );
+}
+
+#[tokio::test]
+async fn station_file_has_every_station_behind_a_state() {
+    env::set_var("NOAA_TOKEN", "test");
+    let name = "station_file_has_every_station_behind_a_state";
+    let http = Http::new(Mode::Replay(fixtures(name).await));
+    let metrics = collect(
+        Noaa::new(Regions::States, http)
+            .with_aggregation(Aggregation::Median)
+            .with_station_file(true),
+    )
+    .await;
+    let annual_temp = &metrics[0];
+    assert_eq!(annual_temp.source().points.as_deref(), Some(STATION_FILE));
+    let points = annual_temp.points().unwrap();
+    assert_eq!(points.len(), 5);
+    assert_eq!(points[2].id, "GHCND:020");
+    assert_eq!(points[2].name, "STATION");
+    assert_eq!(points[2].state, State::Alaska);
+    assert_eq!((points[2].latitude, points[2].longitude), (61.2, -149.9));
+    assert_eq!(points[2].elevation, Some(100.0));
+    // Alaska's median station
+    assert_eq!(annual_temp.get(State::Alaska), Some(points[3].value));
+    // Only annual normals have stations
+    assert_eq!(metrics[10].source().points, None);
+
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let output = written(&dir.join("output"), &metrics);
+    // The fixtures don't have every state, but the stations are all valid
+    let problems = validate(&output, Regions::States).unwrap();
+    assert!(problems.iter().all(|problem| problem.file != STATION_FILE));
+    let stations: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(output.dir().join(STATION_FILE)).unwrap())
+            .unwrap();
+    assert_eq!(stations["GHCND:010"]["values"]["annual_temp"], 50.0);
+    assert_eq!(stations["GHCND:010"]["values"]["freezing_days"], 50.0);
+}
+
+#[tokio::test]
+async fn a_failed_state_is_missing_instead_of_failing_everything() {
+    env::set_var("NOAA_TOKEN", "test");
+    let fixtures = fixtures("a_failed_state_is_missing_instead_of_failing_everything").await;
+    let alaska = format!(
+        "{}&locationid=FIPS:02&datatypeid=ANN-TAVG-NORMAL&offset=1",
+        DATA_URL
+    );
+    std::fs::remove_file(fixture_path(&fixtures, &alaska)).unwrap();
+    let metrics = collect(Noaa::new(
+        Regions::States,
+        Http::new(Mode::Replay(fixtures)),
+    ))
+    .await;
+    let annual_temp = &metrics[0];
+    assert_eq!(annual_temp.get(State::Alabama), Some(55.0));
+    assert_eq!(annual_temp.values()[&State::Alaska], None);
+    assert!(annual_temp.missing()[&State::Alaska]
+        .starts_with("Could not fetch ANN-TAVG-NORMAL for AK (FIPS:02)"));
+    // Not a count of 0
+    assert_eq!(samples(annual_temp, State::Alaska), None);
+    assert_eq!(samples(&metrics[1], State::Alaska), Some(3));
+    assert_eq!(metrics[1].get(State::Alaska), Some(15.0));
+    assert!(metrics[1].missing().get(&State::Alaska).is_none());
+    // Not in NOAA's states at all
+    assert_eq!(annual_temp.values()[&State::Wyoming], None);
+    assert_eq!(
+        annual_temp.missing()[&State::Wyoming],
+        "NOAA has no normals for it"
+    );
+}
+
+#[tokio::test]
+async fn population_needs_the_stations() {
+    env::set_var("NOAA_TOKEN", "test");
+    let fixtures = fixtures("population_needs_the_stations").await;
+    let alaska = format!("{}&locationid=FIPS:02&offset=1", STATIONS_URL);
+    std::fs::remove_file(fixture_path(&fixtures, &alaska)).unwrap();
+    let noaa = |aggregation| {
+        Noaa::new(Regions::States, Http::new(Mode::Replay(fixtures.clone())))
+            .with_aggregation(aggregation)
+    };
+    let metrics = collect(noaa(Aggregation::Population)).await;
+    assert_eq!(metrics[0].get(State::Alabama), Some(52.5));
+    assert!(metrics[0].missing()[&State::Alaska]
+        .starts_with("Could not fetch stations for AK (FIPS:02)"));
+    assert_eq!(samples(&metrics[0], State::Alaska), None);
+    let metrics = collect(noaa(Aggregation::Mean)).await;
+    assert_eq!(metrics[0].get(State::Alaska), Some(15.0));
+}
+
+/// Alaska's three stations, the first two on one page and the last on the next
+async fn page_alaska(fixtures: &Path, second_page: &str) {
+    let station = |index: usize, latitude: f64, longitude: f64| {
+        format!(
+            r#"{{"id":"GHCND:02{}","name":"STATION","latitude":{},"longitude":{},"elevation":100}}"#,
+            index, latitude, longitude
+        )
+    };
+    let url = format!("{}&locationid=FIPS:02", STATIONS_URL);
+    let first = format!(
+        r#"{{"metadata":{{"resultset":{{"offset":1,"count":3,"limit":2}}}},"results":[{},{}]}}"#,
+        station(0, 61.2, -149.9),
+        station(1, 64.8, -147.7)
+    );
+    save_fixture(fixtures, &format!("{}&offset=1", url), &first)
+        .await
+        .unwrap();
+    let second = second_page.replace("{}", &station(2, 58.3, -134.4));
+    save_fixture(fixtures, &format!("{}&offset=3", url), &second)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn stations_are_fetched_a_page_at_a_time() {
+    env::set_var("NOAA_TOKEN", "test");
+    let fixtures = fixtures("stations_are_fetched_a_page_at_a_time").await;
+    page_alaska(
+        &fixtures,
+        r#"{"metadata":{"resultset":{"offset":3,"count":3,"limit":2}},"results":[{}]}"#,
+    )
+    .await;
+    let metrics = collect(
+        Noaa::new(Regions::States, Http::new(Mode::Replay(fixtures)))
+            .with_aggregation(Aggregation::Population),
+    )
+    .await;
+    // Only right with every station located
+    assert_eq!(metrics[0].get(State::Alaska), Some(11.0));
+}
+
+#[tokio::test]
+async fn stations_cut_short_are_missing() {
+    env::set_var("NOAA_TOKEN", "test");
+    let fixtures = fixtures("stations_cut_short_are_missing").await;
+    page_alaska(
+        &fixtures,
+        r#"{"metadata":{"resultset":{"offset":3,"count":3,"limit":2}},"results":[]}"#,
+    )
+    .await;
+    let metrics = collect(
+        Noaa::new(Regions::States, Http::new(Mode::Replay(fixtures)))
+            .with_aggregation(Aggregation::Population),
+    )
+    .await;
+    assert_eq!(metrics[0].get(State::Alabama), Some(52.5));
+    let reason = &metrics[0].missing()[&State::Alaska];
+    assert!(reason.starts_with("Could not fetch stations for AK (FIPS:02)"));
+    assert!(reason.ends_with("stopped after 2"));
+}
+
+#[tokio::test]
+async fn readings_are_fetched_a_page_at_a_time() {
+    env::set_var("NOAA_TOKEN", "test");
+    let fixtures = fixtures("readings_are_fetched_a_page_at_a_time").await;
+    let reading = |index: usize, value: f64| {
+        format!(
+            r#"{{"date":"2010-01-01T00:00:00","datatype":"ANN-TAVG-NORMAL","station":"GHCND:02{}","attributes":"S","value":{}}}"#,
+            index, value
+        )
+    };
+    let url = format!("{}&locationid=FIPS:02&datatypeid=ANN-TAVG-NORMAL", DATA_URL);
+    let first = format!(
+        r#"{{"metadata":{{"resultset":{{"offset":1,"count":3,"limit":2}}}},"results":[{},{}]}}"#,
+        reading(0, 5.0),
+        reading(1, 20.0)
+    );
+    save_fixture(&fixtures, &format!("{}&offset=1", url), &first)
+        .await
+        .unwrap();
+    let second = format!(
+        r#"{{"metadata":{{"resultset":{{"offset":3,"count":3,"limit":2}}}},"results":[{}]}}"#,
+        reading(2, 20.0)
+    );
+    save_fixture(&fixtures, &format!("{}&offset=3", url), &second)
+        .await
+        .unwrap();
+    let metrics = collect(Noaa::new(
+        Regions::States,
+        Http::new(Mode::Replay(fixtures)),
+    ))
+    .await;
+    assert_eq!(metrics[0].get(State::Alaska), Some(15.0));
+    assert_eq!(samples(&metrics[0], State::Alaska), Some(3));
+}
+
+#[tokio::test]
+async fn fails_when_every_state_does() {
+    env::set_var("NOAA_TOKEN", "test");
+    let fixtures = fixtures("fails_when_every_state_does").await;
+    for id in &["FIPS:01", "FIPS:02", "FIPS:11"] {
+        let url = format!(
+            "{}&locationid={}&datatypeid=ANN-SNOW-NORMAL&offset=1",
+            DATA_URL, id
+        );
+        std::fs::remove_file(fixture_path(&fixtures, &url)).unwrap();
+    }
+    let error = Noaa::new(Regions::StatesAndDc, Http::new(Mode::Replay(fixtures)))
+        .collect()
+        .await
+        .unwrap_err();
+    assert!(error
+        .to_string()
+        .starts_with("Could not fetch ANN-SNOW-NORMAL"));
+}
@@ -0,0 +1,193 @@
+//! A small client for the NPS API, https://www.nps.gov/subjects/developer/api-documentation.htm.
+//! Every endpoint (like `parks`, `campgrounds` or `alerts`) returns pages of records, filtered by
+//! params like `parkCode` or `stateCode`, along with the total across every page.
+
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+
+use collector::{fetch_pages, number, Http};
+use states::State;
+
+/// Records per page unless [`Endpoint::limit`] says otherwise. The API's own default is only 50.
+pub const PAGE_SIZE: usize = 500;
+
+/// A request for every record of one endpoint, built up like
+/// `Endpoint::new("alerts").param("parkCode", &["yell", "yose"])`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Endpoint {
+    path: String,
+    params: BTreeMap<String, Vec<String>>,
+    /// Records per page
+    limit: usize,
+}
+
+impl Endpoint {
+    /// `path` is everything after `/api/v1/`, like "parks" or "campgrounds"
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.trim_matches('/').to_owned(),
+            params: BTreeMap::new(),
+            limit: PAGE_SIZE,
+        }
+    }
+
+    /// Only return records matching any of `values`, e.g. `param("stateCode", &["CA", "OR"])`
+    pub fn param<S: AsRef<str>>(mut self, name: &str, values: &[S]) -> Self {
+        self.params
+            .entry(name.to_owned())
+            .or_default()
+            .extend(values.iter().map(|value| value.as_ref().to_owned()));
+        self
+    }
+
+    /// How many records to ask for at once
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit.max(1);
+        self
+    }
+
+    /// The URL of the page of records starting at `start`
+    pub fn url(&self, api_key: &str, start: usize) -> String {
+        let mut url = format!(
+            "https://developer.nps.gov/api/v1/{}?api_key={}",
+            self.path, api_key
+        );
+        for (name, values) in &self.params {
+            url.push_str(&format!("&{}={}", name, values.join(",")));
+        }
+        url.push_str(&format!("&limit={}&start={}", self.limit, start));
+        url
+    }
+
+    /// Every record, fetching as many pages as it takes
+    pub async fn fetch<T: DeserializeOwned>(&self, api_key: &str, http: &Http) -> Result<Vec<T>> {
+        fetch_pages(&format!("{} from NPS", self.path), |start| async move {
+            let page: Page<T> = http
+                .get_json(&self.url(api_key, start), &[("accept", "application/json")])
+                .await
+                .with_context(|| format!("Could not fetch {} from NPS", self.path))?;
+            let total = number(&page.total).ok_or_else(|| {
+                anyhow!("Invalid total {} for {} from NPS", page.total, self.path)
+            })?;
+            Ok(collector::Page {
+                total: total as usize,
+                records: page.data,
+            })
+        })
+        .await
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Page<T> {
+    /// Records across every page, sent as a string
+    total: Value,
+    data: Vec<T>,
+}
+
+/// Every park, monument, historic site, trail and so on that NPS manages
+pub async fn parks(api_key: &str, http: &Http) -> Result<Vec<Park>> {
+    Endpoint::new("parks").fetch(api_key, http).await
+}
+
+/// Every campground in the parks with any of `park_codes`, or in every park if there are none
+pub async fn campgrounds(
+    api_key: &str,
+    http: &Http,
+    park_codes: &[&str],
+) -> Result<Vec<Campground>> {
+    with_park_codes(Endpoint::new("campgrounds"), park_codes)
+        .fetch(api_key, http)
+        .await
+}
+
+/// Every current alert (closures, dangers and the like) in the parks with any of `park_codes`, or
+/// in every park if there are none
+pub async fn alerts(api_key: &str, http: &Http, park_codes: &[&str]) -> Result<Vec<Alert>> {
+    with_park_codes(Endpoint::new("alerts"), park_codes)
+        .fetch(api_key, http)
+        .await
+}
+
+fn with_park_codes(endpoint: Endpoint, park_codes: &[&str]) -> Endpoint {
+    if park_codes.is_empty() {
+        endpoint
+    } else {
+        endpoint.param("parkCode", park_codes)
+    }
+}
+
+/// One park from `/parks`
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Park {
+    pub id: String,
+    /// Unique and used by every other endpoint, like "yell"
+    pub park_code: String,
+    /// Like "Yellowstone National Park"
+    pub full_name: String,
+    /// Like "Yellowstone"
+    pub name: String,
+    /// Like "National Park" or "National Monument", empty for some parks
+    #[serde(default)]
+    pub designation: String,
+    /// Every state (or territory) the park is in
+    #[serde(deserialize_with = "state_list")]
+    pub states: Vec<State>,
+    #[serde(default, deserialize_with = "coordinate")]
+    pub latitude: Option<f64>,
+    #[serde(default, deserialize_with = "coordinate")]
+    pub longitude: Option<f64>,
+    #[serde(default)]
+    pub url: String,
+}
+
+/// One campground from `/campgrounds`
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Campground {
+    pub id: String,
+    pub name: String,
+    /// The park it's in
+    pub park_code: String,
+    #[serde(default, deserialize_with = "coordinate")]
+    pub latitude: Option<f64>,
+    #[serde(default, deserialize_with = "coordinate")]
+    pub longitude: Option<f64>,
+    #[serde(default)]
+    pub url: String,
+}
+
+/// One alert from `/alerts`
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Alert {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    /// Like "Park Closure", "Danger", "Caution" or "Information"
+    pub category: String,
+    /// The park it's about
+    pub park_code: String,
+    #[serde(default)]
+    pub url: String,
+}
+
+/// States are sent as a list of codes like "ID,MT,WY". Anything that isn't a state is skipped.
+fn state_list<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<State>, D::Error> {
+    let codes = String::deserialize(deserializer)?;
+    Ok(codes
+        .split(',')
+        .filter_map(|code| State::from_code(code.trim()))
+        .collect())
+}
+
+/// Coordinates are sent as strings, which are empty when NPS doesn't have them
+fn coordinate<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<f64>, D::Error> {
+    Ok(number(&Value::deserialize(deserializer)?))
+}
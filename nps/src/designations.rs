@@ -0,0 +1,120 @@
+//! The NPS designations counted by each count of parks.
+
+/// A metric counting the parks with any of `designations`
+pub(crate) struct Designation {
+    /// The file in `generated`, without ".json"
+    pub(crate) file: &'static str,
+    pub(crate) name: &'static str,
+    /// Finishes "Number of ..." for the description
+    pub(crate) what: &'static str,
+    pub(crate) designations: &'static [&'static str],
+}
+
+impl Designation {
+    pub(crate) fn matches(&self, designation: &str) -> bool {
+        self.designations.contains(&designation.trim())
+    }
+}
+
+/// NPS isn't consistent about how designations are written (e.g. "National Park & Preserve" and
+/// "National Monument and Preserve"), so each lists every spelling
+pub(crate) static DESIGNATIONS: [Designation; 10] = [
+    Designation {
+        file: "designated_national_parks",
+        name: "Designated National Parks",
+        what: "parks designated a National Park (like Yellowstone or Yosemite)",
+        designations: &[
+            "National Park",
+            "National Parks",
+            "National Park & Preserve",
+            "National Park and Preserve",
+            "National and State Parks",
+        ],
+    },
+    Designation {
+        file: "national_monuments",
+        name: "National Monuments",
+        what: "National Monuments",
+        designations: &[
+            "National Monument",
+            "National Monument & Preserve",
+            "National Monument and Preserve",
+            "National Monument and Historic Shrine",
+        ],
+    },
+    Designation {
+        file: "national_historic_sites",
+        name: "National Historic Sites",
+        what: "National Historic Sites and National Historical Parks",
+        designations: &[
+            "National Historic Site",
+            "National Historical Park",
+            "National Historical Park and Preserve",
+            "National Historic Area",
+            "National Historical Reserve",
+            "International Historic Site",
+        ],
+    },
+    Designation {
+        file: "national_battlefields",
+        name: "National Battlefields",
+        what: "National Battlefields and National Military Parks",
+        designations: &[
+            "National Battlefield",
+            "National Battlefield Park",
+            "National Battlefield Site",
+            "National Military Park",
+        ],
+    },
+    Designation {
+        file: "national_memorials",
+        name: "National Memorials",
+        what: "National Memorials",
+        designations: &["National Memorial"],
+    },
+    Designation {
+        file: "national_seashores",
+        name: "National Seashores and Lakeshores",
+        what: "National Seashores and National Lakeshores",
+        designations: &["National Seashore", "National Lakeshore"],
+    },
+    Designation {
+        file: "national_recreation_areas",
+        name: "National Recreation Areas",
+        what: "National Recreation Areas",
+        designations: &["National Recreation Area"],
+    },
+    Designation {
+        file: "national_preserves",
+        name: "National Preserves",
+        what: "National Preserves and National Reserves",
+        designations: &["National Preserve", "National Reserve"],
+    },
+    Designation {
+        file: "national_rivers",
+        name: "National Rivers",
+        what: "National Rivers and Wild and Scenic Rivers",
+        designations: &[
+            "National River",
+            "National River & Recreation Area",
+            "National River and Recreation Area",
+            "National Recreational River",
+            "National Scenic River",
+            "National Scenic Riverway",
+            "National Scenic Riverways",
+            "Scenic & Recreational River",
+            "Wild & Scenic River",
+            "Wild River",
+        ],
+    },
+    Designation {
+        file: "national_trails",
+        name: "National Trails",
+        what: "National Scenic, Historic and Geologic Trails",
+        designations: &[
+            "National Scenic Trail",
+            "National Historic Trail",
+            "National Geologic Trail",
+        ],
+    },
+];
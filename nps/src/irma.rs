@@ -0,0 +1,155 @@
+//! Acreage and visitation, which aren't in the NPS API. Both come from NPS reports (visitation from
+//! [IRMA](https://irma.nps.gov/Stats/), NPS's visitor use statistics) exported as CSVs with a row
+//! per park, or per park and state, keyed by its four letter unit code (the same as `parkCode` in
+//! the API).
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+
+use metrics::DataType;
+use states::{Resolver, State};
+
+/// A metric summing one column of a CSV over every park in the state
+pub(crate) struct Statistic {
+    /// The file in `generated`, without ".json"
+    pub(crate) file: &'static str,
+    pub(crate) name: &'static str,
+    pub(crate) data_type: DataType,
+    pub(crate) units: &'static str,
+    pub(crate) source_url: &'static str,
+    /// Starts the description, which goes on to say how parks are added up
+    pub(crate) what: &'static str,
+    /// The CSV in the stats dir
+    pub(crate) csv: &'static str,
+    pub(crate) column: &'static str,
+    pub(crate) by_state: ByState,
+}
+
+/// How a park in more than one state is split between them
+pub(crate) enum ByState {
+    /// The CSV has a row for each state a park is in, with its state in this column
+    Column(&'static str),
+    /// The CSV has a row for each park, which is split by its [`Shares`] of acreage, or evenly
+    /// between its states when acreage.csv doesn't have it
+    Acreage,
+}
+
+impl Statistic {
+    /// How parks are added up, which ends the description
+    pub(crate) fn how(&self) -> &'static str {
+        match self.by_state {
+            ByState::Column(_) => "Summed over the part of every park in the state.",
+            ByState::Acreage => {
+                "Summed over every park in the state, splitting parks in more than one state \
+                 (like Yellowstone) by how much of their acreage is in each, rounded to whole \
+                 visits."
+            }
+        }
+    }
+}
+
+/// The fraction of each park's acreage in each of its states, by park code
+pub(crate) type Shares = HashMap<String, Vec<(State, f64)>>;
+
+/// The [`Shares`] of every park in `acreage`'s rows with any acres
+pub(crate) fn shares(acreage: &[Row]) -> Shares {
+    let mut shares = Shares::new();
+    for row in acreage {
+        if let Some(state) = row.state {
+            shares
+                .entry(row.code.clone())
+                .or_default()
+                .push((state, row.value));
+        }
+    }
+    shares.retain(|_code, states| {
+        let total: f64 = states.iter().map(|(_state, acres)| acres).sum();
+        for (_state, acres) in states.iter_mut() {
+            *acres /= total;
+        }
+        total > 0.0
+    });
+    shares
+}
+
+/// One park's value, in one of its states if the CSV says which
+#[derive(Debug, PartialEq)]
+pub(crate) struct Row {
+    /// Lowercase, like `parkCode`
+    pub(crate) code: String,
+    pub(crate) state: Option<State>,
+    pub(crate) value: f64,
+}
+
+/// The CSV whose acreage [`ByState::Acreage`] statistics are split by
+pub(crate) const ACREAGE: &str = "acreage.csv";
+
+/// Acreage comes first, so that visitation can be split by it
+pub(crate) static STATISTICS: [Statistic; 2] = [
+    Statistic {
+        file: "national_park_acreage",
+        name: "National Park Acreage",
+        data_type: DataType::Number,
+        units: "acres",
+        source_url: "https://www.nps.gov/subjects/landresourcesoffice/acreagereports.htm",
+        what: "Gross acres of National Park Service land, from the NPS Land Resources Division's \
+               acreage by state.",
+        csv: ACREAGE,
+        column: "GrossAreaAcres",
+        by_state: ByState::Column("State"),
+    },
+    Statistic {
+        file: "national_park_visits",
+        name: "National Park Visits",
+        data_type: DataType::Integer,
+        units: "visits",
+        source_url: "https://irma.nps.gov/Stats/",
+        what: "Recreation visits to National Park Service parks in a year, from NPS visitor use statistics.",
+        csv: "visitation.csv",
+        column: "RecreationVisits",
+        by_state: ByState::Acreage,
+    },
+];
+
+/// Every row of `statistic`'s CSV with a number in its column. Numbers can have thousands
+/// separators, and states can be names or postal codes. Rows whose state isn't one are skipped with
+/// a warning.
+pub(crate) fn parse(body: &str, statistic: &Statistic) -> Result<Vec<Row>> {
+    let mut reader = csv::Reader::from_reader(body.as_bytes());
+    let headers = reader.headers()?.clone();
+    let position = |name: &str| {
+        headers
+            .iter()
+            .position(|header| header.trim() == name)
+            .ok_or_else(|| anyhow!("Missing the {} column", name))
+    };
+    let (code, column) = (position("UnitCode")?, position(statistic.column)?);
+    let state_column = match statistic.by_state {
+        ByState::Column(name) => Some(position(name)?),
+        ByState::Acreage => None,
+    };
+    let mut resolver = Resolver::new();
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let value = match record[column].trim().replace(',', "").parse() {
+            Ok(value) => value,
+            Err(_) => continue,
+        };
+        let state = match state_column {
+            Some(state) => match resolver.resolve(&record[state]) {
+                Some(state) => Some(state),
+                None => continue,
+            },
+            None => None,
+        };
+        rows.push(Row {
+            code: record[code].trim().to_lowercase(),
+            state,
+            value,
+        });
+    }
+    resolver.warn_unresolved(statistic.csv);
+    Ok(rows)
+}
@@ -0,0 +1,236 @@
+#![forbid(unsafe_code)]
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::env;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use futures::future::join_all;
+
+use collector::{Collector, Http};
+
+use api::Park;
+use designations::{Designation, DESIGNATIONS};
+use irma::{Row, Shares, Statistic, ACREAGE, STATISTICS};
+use metrics::{DataType, Item, Items, Metric, Sort, Source};
+use states::{Regions, State};
+
+pub mod api;
+mod designations;
+mod irma;
+
+/// Every park counted in each state, for each count metric, so the front end can list them
+pub const PARK_FILE: &str = "nps_parks.json";
+
+static NATIONAL_PARKS: Source = Source {
+    source: Cow::Borrowed("national_parks.json"),
+    name: Cow::Borrowed("National Parks"),
+    data_type: DataType::Integer,
+    source_url: Cow::Borrowed("https://www.nps.gov/subjects/developer/index.htm"),
+    description: Cow::Borrowed(
+        "Total number of National Park Service parks in the state, including monuments, historic sites, trails and every other designation.",
+    ),
+    units: Some(Cow::Borrowed("parks")),
+    sort: Sort::Descending,
+    items: Some(Cow::Borrowed(PARK_FILE)),
+    ..Source::BASE
+};
+
+/// Every park in each state, once each, by park code
+type Parks<'a> = BTreeMap<State, BTreeMap<&'a str, &'a Park>>;
+
+/// Everything fetched: every park, and the contents of each of [`irma::STATISTICS`]' CSVs (or why
+/// they couldn't be read)
+pub struct Raw {
+    parks: Vec<Park>,
+    statistics: Vec<Result<String>>,
+}
+
+/// Counts the parks in every state, in total and by designation, and adds up their acreage and
+/// visitation
+pub struct Nps {
+    regions: Regions,
+    http: Http,
+    stats_dir: PathBuf,
+}
+
+impl Nps {
+    /// Acreage and visitation are read from CSVs in `raw_data/nps`, see [`Nps::with_stats_dir`]
+    pub fn new(regions: Regions, http: Http) -> Self {
+        Self {
+            regions,
+            http,
+            stats_dir: PathBuf::from("raw_data/nps"),
+        }
+    }
+
+    /// Read acreage.csv and visitation.csv from somewhere else. Either one missing only skips its
+    /// metric.
+    pub fn with_stats_dir(mut self, stats_dir: impl Into<PathBuf>) -> Self {
+        self.stats_dir = stats_dir.into();
+        self
+    }
+
+    /// How many parks are in each state, with the parks themselves as the metric's items
+    fn count(&self, source: Source, parks: &Parks, counted: impl Fn(&Park) -> bool) -> Metric {
+        let items: Items = parks
+            .iter()
+            .map(|(state, parks)| {
+                let mut items: Vec<Item> = parks
+                    .values()
+                    .filter(|park| counted(park))
+                    .map(|park| Item {
+                        id: park.park_code.clone(),
+                        name: park.full_name.clone(),
+                    })
+                    .collect();
+                items.sort_by(|a, b| a.name.cmp(&b.name));
+                (*state, items)
+            })
+            .collect();
+        let values: Vec<(State, f64)> = items
+            .iter()
+            .map(|(state, items)| (*state, items.len() as f64))
+            .collect();
+        Metric::new(source, values).with_items(PARK_FILE, items)
+    }
+
+    /// The total of `statistic` over every park in each state. Each state only gets its part of a
+    /// park in several, see [`irma::ByState`].
+    fn sum(&self, statistic: &Statistic, rows: &[Row], parks: &Parks, shares: &Shares) -> Metric {
+        let mut totals: BTreeMap<State, f64> = parks.keys().map(|state| (*state, 0.0)).collect();
+        for row in rows {
+            let park = match parks
+                .values()
+                .find_map(|parks| parks.get(row.code.as_str()))
+            {
+                Some(park) => park,
+                // Not one of the parks counted
+                None => continue,
+            };
+            let split = match (row.state, shares.get(&row.code)) {
+                (Some(state), _) => vec![(state, 1.0)],
+                (None, Some(shares)) => shares.clone(),
+                (None, None) => {
+                    let share = 1.0 / park.states.len() as f64;
+                    park.states.iter().map(|state| (*state, share)).collect()
+                }
+            };
+            for (state, share) in split {
+                if let Some(total) = totals.get_mut(&state) {
+                    *total += row.value * share;
+                }
+            }
+        }
+        if statistic.data_type == DataType::Integer {
+            for total in totals.values_mut() {
+                *total = total.round();
+            }
+        }
+        Metric::new(statistic_source(statistic), totals)
+    }
+}
+
+/// Every row of `statistic`'s CSV, or `None` (with a warning) if it couldn't be read
+fn rows(statistic: &Statistic, csv: Result<String>) -> Option<Vec<Row>> {
+    csv.and_then(|csv| {
+        irma::parse(&csv, statistic).with_context(|| format!("Invalid {}", statistic.csv))
+    })
+    .map_err(|error| eprintln!("Warning: {:#}, skipping {}", error, statistic.file))
+    .ok()
+}
+
+/// The count of parks with one of the designation's designations
+fn designation_source(designation: &Designation) -> Source {
+    Source {
+        source: format!("{}.json", designation.file).into(),
+        name: Cow::Borrowed(designation.name),
+        description: format!("Number of {} in the state.", designation.what).into(),
+        min: Some(0.0),
+        ..NATIONAL_PARKS.clone()
+    }
+}
+
+fn statistic_source(statistic: &Statistic) -> Source {
+    Source {
+        source: format!("{}.json", statistic.file).into(),
+        name: Cow::Borrowed(statistic.name),
+        data_type: statistic.data_type,
+        source_url: Cow::Borrowed(statistic.source_url),
+        description: format!("{} {}", statistic.what, statistic.how()).into(),
+        units: Some(Cow::Borrowed(statistic.units)),
+        min: Some(0.0),
+        items: None,
+        ..NATIONAL_PARKS.clone()
+    }
+}
+
+#[async_trait]
+impl Collector for Nps {
+    type Raw = Raw;
+
+    fn name(&self) -> &'static str {
+        "nps"
+    }
+
+    fn required_env(&self) -> &'static [&'static str] {
+        &["NPS_KEY"]
+    }
+
+    fn sources(&self) -> Vec<Source> {
+        std::iter::once(NATIONAL_PARKS.clone())
+            .chain(DESIGNATIONS.iter().map(designation_source))
+            .chain(STATISTICS.iter().map(statistic_source))
+            .collect()
+    }
+
+    async fn fetch(&self) -> Result<Self::Raw> {
+        let api_key = env::var("NPS_KEY")?;
+        let parks = api::parks(&api_key, &self.http).await?;
+        let statistics = join_all(STATISTICS.iter().map(|statistic| {
+            let path = self.stats_dir.join(statistic.csv);
+            async move {
+                tokio::fs::read_to_string(&path)
+                    .await
+                    .with_context(|| format!("Could not read {}", path.display()))
+            }
+        }))
+        .await;
+        Ok(Raw { parks, statistics })
+    }
+
+    fn parse(&self, raw: Self::Raw) -> Result<Vec<Metric>> {
+        let mut parks_by_state: Parks = self
+            .regions
+            .iter()
+            .map(|state| (state, BTreeMap::new()))
+            .collect();
+        for park in &raw.parks {
+            for state in &park.states {
+                if let Some(state_parks) = parks_by_state.get_mut(state) {
+                    state_parks.insert(park.park_code.as_str(), park);
+                }
+            }
+        }
+        let mut metrics = vec![self.count(NATIONAL_PARKS.clone(), &parks_by_state, |_| true)];
+        for designation in &DESIGNATIONS {
+            metrics.push(
+                self.count(designation_source(designation), &parks_by_state, |park| {
+                    designation.matches(&park.designation)
+                }),
+            );
+        }
+        let mut shares = Shares::new();
+        for (statistic, csv) in STATISTICS.iter().zip(raw.statistics) {
+            if let Some(rows) = rows(statistic, csv) {
+                metrics.push(self.sum(statistic, &rows, &parks_by_state, &shares));
+                if statistic.csv == ACREAGE {
+                    shares = irma::shares(&rows);
+                }
+            }
+        }
+        Ok(metrics)
+    }
+}
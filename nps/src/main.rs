@@ -1,39 +1,12 @@
 #![forbid(unsafe_code)]
 
-use std::collections::{HashMap, HashSet};
-use std::env;
-
 use anyhow::Result;
 use dotenv::dotenv;
-use serde::Deserialize;
-use tokio::fs;
-
-use states::STATES;
-
-#[derive(Debug, Deserialize)]
-struct Park {
-    states: String,
-    #[serde(rename = "parkCode")]
-    park_code: String,
-    designation: String,
-}
-
-impl Park {
-    fn get_states(&self) -> Vec<&str> {
-        self.states.split(',').collect()
-    }
-}
 
-#[derive(Debug, Deserialize)]
-struct Category {
-    name: String,
-    parks: Vec<Park>,
-}
-
-#[derive(Debug, Deserialize)]
-struct Response {
-    data: Vec<Category>,
-}
+use collector::{AnyCollector, Http};
+use metrics::Output;
+use nps::Nps;
+use states::Regions;
 
 #[tokio::main]
 async fn main() {
@@ -45,50 +18,8 @@ async fn main() {
 }
 
 async fn run() -> Result<()> {
-    let categories = get_categories().await?;
-    let parks: Vec<Park> = categories
-        .into_iter()
-        .flat_map(|category| category.parks)
-        .collect();
-    let mut parks_by_state: HashMap<&str, HashSet<&str>> = STATES
-        .iter()
-        .map(|state| (*state, HashSet::new()))
-        .collect();
-    for park in parks.iter() {
-        let park_code = park.park_code.as_str();
-        for state in park.get_states() {
-            if let Some(park_set) = parks_by_state.get_mut(state) {
-                park_set.insert(park_code);
-            }
-        }
-    }
-    let result: HashMap<&'static str, usize> = parks_by_state
-        .into_iter()
-        .map(|(state, parks)| (state, parks.len()))
-        .collect();
-    fs::write(
-        "generated/national_parks.json",
-        serde_json::to_string(&result)?,
-    )
-    .await?;
-    Ok(())
-}
-
-async fn get_categories() -> Result<Vec<Category>> {
-    let client = reqwest::Client::new();
-    let api_key = env::var("NPS_KEY")?;
-    let uri = format!(
-        "https://developer.nps.gov/api/v1/activities/parks?api_key={}",
-        api_key
-    );
-    let response: Response = client
-        .get(&uri)
-        .header("accept", "application/json")
-        .send()
-        .await?
-        .json()
+    let metrics = Nps::new(Regions::from_env()?, Http::from_env()?)
+        .collect()
         .await?;
-    Ok(response.data)
+    Output::default().write(&metrics)
 }
-
-// curl -X GET "" -H  "accept: application/json"
@@ -0,0 +1,158 @@
+use std::path::{Path, PathBuf};
+
+use collector::{save_fixture, Http, Mode};
+use nps::api::{self, Endpoint, PAGE_SIZE};
+use states::State;
+
+/// A page of `total` parks, one per code
+fn page(total: usize, codes: &[&str]) -> String {
+    let parks: Vec<String> = codes
+        .iter()
+        .map(|code| {
+            format!(
+                r#"{{"id":"{0}","parkCode":"{0}","fullName":"{0} Park","name":"{0}","states":"CA"}}"#,
+                code
+            )
+        })
+        .collect();
+    format!(
+        r#"{{"total":"{}","limit":"2","start":"0","data":[{}]}}"#,
+        total,
+        parks.join(",")
+    )
+}
+
+async fn fixtures(name: &str, pages: &[(&Endpoint, usize, String)]) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    for (endpoint, start, body) in pages {
+        save_fixture(&dir, &endpoint.url("test", *start), body)
+            .await
+            .unwrap();
+    }
+    dir
+}
+
+#[test]
+fn endpoint_urls() {
+    assert_eq!(
+        Endpoint::new("/parks/").url("secret", 0),
+        format!(
+            "https://developer.nps.gov/api/v1/parks?api_key=secret&limit={}&start=0",
+            PAGE_SIZE
+        )
+    );
+    assert_eq!(
+        Endpoint::new("alerts")
+            .param("stateCode", &["CA"])
+            .param("parkCode", &["yell", "yose"])
+            .limit(0)
+            .url("secret", 10),
+        "https://developer.nps.gov/api/v1/alerts?api_key=secret&parkCode=yell,yose&stateCode=CA\
+         &limit=1&start=10"
+    );
+}
+
+#[tokio::test]
+async fn every_page_is_fetched() {
+    let endpoint = Endpoint::new("parks").limit(2);
+    let dir = fixtures(
+        "every_page_is_fetched",
+        &[
+            (&endpoint, 0, page(5, &["a", "b"])),
+            (&endpoint, 2, page(5, &["c", "d"])),
+            (&endpoint, 4, page(5, &["e"])),
+        ],
+    )
+    .await;
+    let http = Http::new(Mode::Replay(dir));
+    let parks: Vec<api::Park> = endpoint.fetch("test", &http).await.unwrap();
+    let codes: Vec<&str> = parks.iter().map(|park| park.park_code.as_str()).collect();
+    assert_eq!(codes, ["a", "b", "c", "d", "e"]);
+}
+
+#[tokio::test]
+async fn an_empty_page_before_the_total_fails() {
+    let endpoint = Endpoint::new("parks").limit(2);
+    let dir = fixtures(
+        "an_empty_page_before_the_total_fails",
+        &[
+            (&endpoint, 0, page(10, &["a", "b"])),
+            (&endpoint, 2, page(10, &[])),
+        ],
+    )
+    .await;
+    let http = Http::new(Mode::Replay(dir));
+    let error = endpoint
+        .fetch::<api::Park>("test", &http)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Expected 10 parks from NPS but the pages stopped after 2"
+    );
+}
+
+#[tokio::test]
+async fn parks_are_typed() {
+    let body = r#"{"total":"2","data":[
+        {"id":"1","parkCode":"yell","fullName":"Yellowstone National Park","name":"Yellowstone",
+         "designation":"National Park","states":"ID,MT,WY","latitude":"44.59824417",
+         "longitude":"-110.5471695","url":"https://www.nps.gov/yell/index.htm"},
+        {"id":"2","parkCode":"xyzz","fullName":"Nowhere","name":"Nowhere","designation":"",
+         "states":"ZZ,GU","latitude":"","longitude":""}
+    ]}"#;
+    let dir = fixtures(
+        "parks_are_typed",
+        &[(&Endpoint::new("parks"), 0, body.to_owned())],
+    )
+    .await;
+    let parks = api::parks("test", &Http::new(Mode::Replay(dir)))
+        .await
+        .unwrap();
+    assert_eq!(parks[0].full_name, "Yellowstone National Park");
+    assert_eq!(parks[0].designation, "National Park");
+    assert_eq!(
+        parks[0].states,
+        [State::Idaho, State::Montana, State::Wyoming]
+    );
+    assert_eq!(parks[0].latitude, Some(44.59824417));
+    assert_eq!(parks[0].longitude, Some(-110.5471695));
+    assert_eq!(parks[1].states, [State::Guam]);
+    assert_eq!(parks[1].latitude, None);
+    assert_eq!(parks[1].url, "");
+}
+
+#[tokio::test]
+async fn campgrounds_and_alerts_are_filtered_by_park() {
+    let campgrounds = r#"{"total":"1","data":[
+        {"id":"c1","name":"Madison Campground","parkCode":"yell","latitude":"44.6456",
+         "longitude":"-110.8605"}
+    ]}"#;
+    let alerts = r#"{"total":"1","data":[
+        {"id":"a1","title":"Road Closed","description":"Snow","category":"Park Closure",
+         "parkCode":"yose","url":""}
+    ]}"#;
+    let dir = fixtures(
+        "campgrounds_and_alerts_are_filtered_by_park",
+        &[
+            (
+                &Endpoint::new("campgrounds").param("parkCode", &["yell", "yose"]),
+                0,
+                campgrounds.to_owned(),
+            ),
+            (&Endpoint::new("alerts"), 0, alerts.to_owned()),
+        ],
+    )
+    .await;
+    let http = Http::new(Mode::Replay(dir));
+    let campgrounds = api::campgrounds("test", &http, &["yell", "yose"])
+        .await
+        .unwrap();
+    assert_eq!(campgrounds[0].name, "Madison Campground");
+    assert_eq!(campgrounds[0].park_code, "yell");
+    assert_eq!(campgrounds[0].latitude, Some(44.6456));
+    let alerts = api::alerts("test", &http, &[]).await.unwrap();
+    assert_eq!(alerts[0].category, "Park Closure");
+    assert_eq!(alerts[0].park_code, "yose");
+}
@@ -0,0 +1,170 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use collector::testing::{collect, fixtures, metric, written};
+use metrics::validate;
+use nps::{Nps, PARK_FILE};
+use states::{Regions, State};
+
+fn replay(regions: Regions) -> Nps {
+    env::set_var("NPS_KEY", "test");
+    Nps::new(regions, fixtures()).with_stats_dir("tests/no_stats")
+}
+
+/// Acreage of every park in each of its states, with the separators and case of NPS's own reports
+fn stats_dir(name: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("acreage.csv"),
+        "State,UnitCode,ParkName,GrossAreaAcres\n\
+         ID,YELL,Yellowstone NP,\"31,488.00\"\n\
+         MT,YELL,Yellowstone NP,\"167,624.71\"\n\
+         WY,YELL,Yellowstone NP,\"2,020,678.00\"\n\
+         California,YOSE,Yosemite NP,\"761,747.50\"\n\
+         CA,JOTR,Joshua Tree NP,\"795,155.85\"\n\
+         North Carolina,GRSM,Great Smoky Mountains NP,\"275,895.00\"\n\
+         Tennessee,GRSM,Great Smoky Mountains NP,\"246,081.00\"\n\
+         VI,VIIS,Virgin Islands NP,\"15,052.33\"\n\
+         Nowhere,YOSE,Yosemite NP,1\n",
+    )
+    .unwrap();
+    dir
+}
+
+#[tokio::test]
+async fn counts_distinct_parks_per_state() {
+    let metrics = collect(replay(Regions::States)).await;
+    // No acreage or visitation
+    assert_eq!(metrics.len(), 11);
+    let parks = &metrics[0];
+    assert_eq!(parks.source().source, "national_parks.json");
+    assert_eq!(parks.get(State::California), Some(2.0));
+    assert_eq!(parks.get(State::Wyoming), Some(1.0));
+    assert_eq!(parks.get(State::Montana), Some(1.0));
+    assert_eq!(parks.get(State::Maryland), Some(0.0));
+    assert_eq!(parks.values().len(), 50);
+}
+
+#[tokio::test]
+async fn territories_follow_regions() {
+    let states = collect(replay(Regions::States)).await;
+    assert!(!states[0].values().contains_key(&State::DistrictOfColumbia));
+
+    let all = collect(replay(Regions::All)).await;
+    assert_eq!(all[0].get(State::DistrictOfColumbia), Some(1.0));
+    assert_eq!(all[0].get(State::VirginIslands), Some(1.0));
+}
+
+#[tokio::test]
+async fn counts_parks_by_designation() {
+    let metrics = collect(replay(Regions::All)).await;
+    let designated = metric(&metrics, "designated_national_parks.json");
+    assert_eq!(designated.get(State::California), Some(2.0));
+    assert_eq!(designated.get(State::Idaho), Some(1.0));
+    // The National Mall has no designation, so it's only in the total
+    assert_eq!(designated.get(State::DistrictOfColumbia), Some(0.0));
+    assert_eq!(metrics[0].get(State::DistrictOfColumbia), Some(1.0));
+    assert_eq!(
+        metric(&metrics, "national_monuments.json").get(State::California),
+        Some(0.0)
+    );
+    let california = &designated.items().unwrap()[&State::California];
+    let names: Vec<&str> = california.iter().map(|item| &*item.name).collect();
+    assert_eq!(
+        names,
+        ["Joshua Tree National Park", "Yosemite National Park"]
+    );
+    assert_eq!(california[1].id, "yose");
+}
+
+#[tokio::test]
+async fn sums_acreage_of_every_park() {
+    let dir = stats_dir("sums_acreage_of_every_park");
+    let metrics = collect(replay(Regions::States).with_stats_dir(&dir)).await;
+    // Visitation is missing, so only acreage is added
+    assert_eq!(metrics.len(), 12);
+    let acreage = metric(&metrics, "national_park_acreage.json");
+    assert_eq!(acreage.get(State::California), Some(761_747.5 + 795_155.85));
+    // Only the part of Yellowstone in each state
+    assert_eq!(acreage.get(State::Montana), Some(167_624.71));
+    assert_eq!(acreage.get(State::Wyoming), Some(2_020_678.0));
+    assert_eq!(acreage.get(State::Maryland), Some(0.0));
+    assert_eq!(acreage.source().units.as_deref(), Some("acres"));
+    assert_eq!(acreage.source().items, None);
+
+    fs::write(
+        dir.join("visitation.csv"),
+        "UnitCode,Year,RecreationVisits\nYOSE,2023,\"3,897,070\"\nJOTR,2023,\"3,270,404\"\n",
+    )
+    .unwrap();
+    let metrics = collect(replay(Regions::States).with_stats_dir(&dir)).await;
+    let visits = metric(&metrics, "national_park_visits.json");
+    assert_eq!(visits.get(State::California), Some(7_167_474.0));
+}
+
+#[tokio::test]
+async fn a_park_in_two_states_is_not_counted_twice() {
+    let dir = stats_dir("a_park_in_two_states_is_not_counted_twice");
+    fs::write(
+        dir.join("visitation.csv"),
+        "UnitCode,Year,RecreationVisits\nGRSM,2023,\"13,297,647\"\nJOTR,2023,\"3,270,404\"\n",
+    )
+    .unwrap();
+    let metrics = collect(replay(Regions::States).with_stats_dir(&dir)).await;
+    assert_eq!(metrics[0].get(State::NorthCarolina), Some(1.0));
+    assert_eq!(metrics[0].get(State::Tennessee), Some(1.0));
+    let acreage = metric(&metrics, "national_park_acreage.json");
+    assert_eq!(acreage.get(State::NorthCarolina), Some(275_895.0));
+    assert_eq!(acreage.get(State::Tennessee), Some(246_081.0));
+    let total: f64 = acreage.values().values().flatten().sum();
+    assert!((total - (2_219_790.71 + 761_747.5 + 795_155.85 + 521_976.0)).abs() < 1e-6);
+    // Its visits aren't reported by state, so they're split by its acreage
+    let visits = metric(&metrics, "national_park_visits.json");
+    assert_eq!(visits.get(State::NorthCarolina), Some(7_028_588.0));
+    assert_eq!(visits.get(State::Tennessee), Some(6_269_059.0));
+    assert_eq!(visits.get(State::California), Some(3_270_404.0));
+    assert!(visits
+        .source()
+        .description
+        .ends_with("by how much of their acreage is in each, rounded to whole visits."));
+}
+
+#[tokio::test]
+async fn visits_are_split_evenly_without_acreage() {
+    let dir = stats_dir("visits_are_split_evenly_without_acreage");
+    fs::remove_file(dir.join("acreage.csv")).unwrap();
+    fs::write(
+        dir.join("visitation.csv"),
+        "UnitCode,Year,RecreationVisits\nYELL,2023,\"4,501,382\"\nGRSM,2023,\"13,297,647\"\n",
+    )
+    .unwrap();
+    let metrics = collect(replay(Regions::States).with_stats_dir(&dir)).await;
+    let visits = metric(&metrics, "national_park_visits.json");
+    assert_eq!(visits.get(State::Idaho), Some(1_500_461.0));
+    assert_eq!(visits.get(State::Montana), Some(1_500_461.0));
+    assert_eq!(visits.get(State::Wyoming), Some(1_500_461.0));
+    // Half a visit rounds up in both
+    assert_eq!(visits.get(State::NorthCarolina), Some(6_648_824.0));
+    assert_eq!(visits.get(State::Tennessee), Some(6_648_824.0));
+}
+
+#[tokio::test]
+async fn every_count_lists_its_parks() {
+    let name = "every_count_lists_its_parks";
+    let metrics = collect(replay(Regions::States).with_stats_dir(stats_dir(name))).await;
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let output = written(&dir.join("output"), &metrics);
+    let parks: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(output.dir().join(PARK_FILE)).unwrap()).unwrap();
+    assert_eq!(parks["WY"]["national_parks"][0]["id"], "yell");
+    assert_eq!(
+        parks["CA"]["designated_national_parks"][1]["name"],
+        "Yosemite National Park"
+    );
+    assert_eq!(parks["MD"]["national_trails"], serde_json::json!([]));
+    assert_eq!(output.read("national_parks.json").unwrap(), metrics[0]);
+    assert_eq!(validate(&output, Regions::States).unwrap(), []);
+}
@@ -0,0 +1,153 @@
+#![forbid(unsafe_code)]
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use futures::future::try_join_all;
+use serde::Deserialize;
+use time::Date;
+
+use collector::Collector;
+use metrics::{DataType, Metric, Sort, Source};
+use states::{Regions, State};
+
+static SOLAR_PRICES: Source = Source {
+    source: Cow::Borrowed("solar_prices.json"),
+    name: Cow::Borrowed("Solar Installation Cost"),
+    data_type: DataType::Money,
+    source_url: Cow::Borrowed("https://emp.lbl.gov/tracking-the-sun"),
+    description: Cow::Borrowed(
+        "The average cost of installing solar panels over the last 10 years.",
+    ),
+    units: Some(Cow::Borrowed("$/KW")),
+    sort: Sort::Ascending,
+    ..Source::BASE
+};
+
+#[derive(Debug, Deserialize)]
+struct Record {
+    #[serde(rename = "System Size")]
+    size: f64,
+    #[serde(rename = "Total Installed Price")]
+    price: f64,
+    #[serde(rename = "State")]
+    state: String,
+    /// Never read, but parsing it means records with invalid dates get skipped
+    #[allow(dead_code)]
+    #[serde(rename = "Installation Date")]
+    #[serde(with = "parse_date")]
+    date: Date,
+}
+
+mod parse_date {
+    use serde::{self, Deserialize, Deserializer};
+    use time::Date;
+
+    const FORMAT: &str = "%-m/%-d/%Y";
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Date, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Date::parse(&s, FORMAT).map_err(serde::de::Error::custom)
+    }
+}
+
+/// The CSV files Tracking the Sun is split into
+static PARTS: [&str; 2] = ["part_1.csv", "part_2.csv"];
+
+fn load_data(path: &Path, regions: Regions) -> Result<HashMap<State, Vec<f64>>> {
+    let file = File::open(path).with_context(|| format!("Could not open {}", path.display()))?;
+    let mut rdr = csv::Reader::from_reader(file);
+    let mut prices: HashMap<State, Vec<f64>> =
+        regions.iter().map(|state| (state, Vec::new())).collect();
+    for result in rdr.deserialize() {
+        if result.is_err() {
+            continue;
+        }
+        let record: Record = result?;
+        #[allow(clippy::float_cmp)]
+        if record.price == 0.0 || record.price == -9999.0 {
+            continue;
+        }
+        #[allow(clippy::float_cmp)]
+        if record.size == 0.0 || record.size == -9999.0 {
+            continue;
+        }
+        let price = record.price / record.size;
+        let state = State::from_code(&record.state)
+            .ok_or_else(|| anyhow!("Missing state {}!", record.state))?;
+        if !regions.contains(state) {
+            continue;
+        }
+        prices.entry(state).or_default().push(price);
+    }
+    Ok(prices)
+}
+
+/// Averages the price per KW of every installation in each state
+pub struct SolarPrices {
+    regions: Regions,
+    dir: PathBuf,
+}
+
+impl SolarPrices {
+    /// Requires the Tracking the Sun CSVs to be downloaded into `raw_data/tracking-the-sun`.
+    pub fn new(regions: Regions) -> Self {
+        Self::from_dir(regions, "raw_data/tracking-the-sun")
+    }
+
+    /// Load the Tracking the Sun CSVs (part_1.csv and part_2.csv) from somewhere else.
+    pub fn from_dir(regions: Regions, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            regions,
+            dir: dir.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl Collector for SolarPrices {
+    /// Price per KW of every installation, by state, for each CSV
+    type Raw = Vec<HashMap<State, Vec<f64>>>;
+
+    fn name(&self) -> &'static str {
+        "solar_prices"
+    }
+
+    fn sources(&self) -> Vec<Source> {
+        vec![SOLAR_PRICES.clone()]
+    }
+
+    async fn fetch(&self) -> Result<Self::Raw> {
+        let regions = self.regions;
+        let loads = PARTS.iter().map(|part| {
+            let path = self.dir.join(part);
+            async move { tokio::task::spawn_blocking(move || load_data(&path, regions)).await? }
+        });
+        try_join_all(loads).await
+    }
+
+    fn parse(&self, parts: Self::Raw) -> Result<Vec<Metric>> {
+        let averages = self.regions.iter().map(|state| {
+            let prices: Vec<f64> = parts
+                .iter()
+                .filter_map(|part| part.get(&state))
+                .flatten()
+                .copied()
+                .collect();
+            let average = if prices.is_empty() {
+                None
+            } else {
+                Some(prices.iter().sum::<f64>() / prices.len() as f64)
+            };
+            (state, average)
+        });
+        Ok(vec![Metric::new(SOLAR_PRICES.clone(), averages)])
+    }
+}
@@ -0,0 +1,24 @@
+use collector::testing::collect;
+use solar_prices::SolarPrices;
+use states::{Regions, State};
+
+#[tokio::test]
+async fn averages_price_per_kw_across_parts() {
+    let metrics = collect(SolarPrices::from_dir(Regions::States, "tests/fixtures")).await;
+    let prices = &metrics[0];
+    // 4000, 3000 and 3500 per KW, the invalid price and the invalid date are skipped
+    assert_eq!(prices.get(State::California), Some(3500.0));
+    // The invalid size is skipped
+    assert_eq!(prices.get(State::Maryland), Some(3000.0));
+    assert_eq!(prices.get(State::Texas), None);
+    assert_eq!(prices.values().len(), 50);
+}
+
+#[tokio::test]
+async fn territories_follow_regions() {
+    let states = collect(SolarPrices::from_dir(Regions::States, "tests/fixtures")).await;
+    assert!(!states[0].values().contains_key(&State::PuertoRico));
+
+    let all = collect(SolarPrices::from_dir(Regions::All, "tests/fixtures")).await;
+    assert_eq!(all[0].get(State::PuertoRico), Some(2500.0));
+}
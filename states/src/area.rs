@@ -0,0 +1,66 @@
+use crate::State;
+
+impl State {
+    /// Land area (not counting water) in square miles, from the Census Bureau's 2010 State Area
+    /// Measurements https://www.census.gov/geographies/reference-files/2010/geo/state-area.html
+    pub fn land_area(self) -> f64 {
+        match self {
+            State::Alabama => 50_645.33,
+            State::Alaska => 570_640.95,
+            State::Arizona => 113_594.08,
+            State::Arkansas => 52_035.48,
+            State::California => 155_779.22,
+            State::Colorado => 103_641.89,
+            State::Connecticut => 4_842.36,
+            State::Delaware => 1_948.54,
+            State::Florida => 53_624.76,
+            State::Georgia => 57_513.49,
+            State::Hawaii => 6_422.63,
+            State::Idaho => 82_643.12,
+            State::Illinois => 55_518.93,
+            State::Indiana => 35_826.11,
+            State::Iowa => 55_857.13,
+            State::Kansas => 81_758.72,
+            State::Kentucky => 39_486.34,
+            State::Louisiana => 43_203.90,
+            State::Maine => 30_842.92,
+            State::Maryland => 9_707.24,
+            State::Massachusetts => 7_800.06,
+            State::Michigan => 56_538.90,
+            State::Minnesota => 79_626.74,
+            State::Mississippi => 46_923.27,
+            State::Missouri => 68_741.52,
+            State::Montana => 145_545.80,
+            State::Nebraska => 76_824.17,
+            State::Nevada => 109_781.18,
+            State::NewHampshire => 8_952.65,
+            State::NewJersey => 7_354.22,
+            State::NewMexico => 121_298.15,
+            State::NewYork => 47_126.40,
+            State::NorthCarolina => 48_617.91,
+            State::NorthDakota => 69_000.80,
+            State::Ohio => 40_860.69,
+            State::Oklahoma => 68_594.92,
+            State::Oregon => 95_988.01,
+            State::Pennsylvania => 44_742.70,
+            State::RhodeIsland => 1_033.81,
+            State::SouthCarolina => 30_060.70,
+            State::SouthDakota => 75_811.00,
+            State::Tennessee => 41_234.90,
+            State::Texas => 261_231.71,
+            State::Utah => 82_169.62,
+            State::Vermont => 9_216.66,
+            State::Virginia => 39_490.09,
+            State::Washington => 66_455.52,
+            State::WestVirginia => 24_038.21,
+            State::Wisconsin => 54_157.80,
+            State::Wyoming => 97_093.14,
+            State::DistrictOfColumbia => 61.05,
+            State::PuertoRico => 3_423.78,
+            State::Guam => 209.80,
+            State::VirginIslands => 134.32,
+            State::AmericanSamoa => 76.46,
+            State::NorthernMarianaIslands => 182.33,
+        }
+    }
+}
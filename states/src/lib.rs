@@ -1,63 +1,217 @@
 #![forbid(unsafe_code)]
 
-use phf::phf_map;
-
-pub static STATES_BY_NAME: phf::Map<&'static str, &'static str> = phf_map! {
-  "Alabama" => "AL",
-  "Alaska" => "AK",
-  "Arizona" => "AZ",
-  "Arkansas" => "AR",
-  "California" => "CA",
-  "Colorado" => "CO",
-  "Connecticut" => "CT",
-  "Delaware" => "DE",
-  "Florida" => "FL",
-  "Georgia" => "GA",
-  "Hawaii" => "HI",
-  "Idaho" => "ID",
-  "Illinois" => "IL",
-  "Indiana" => "IN",
-  "Iowa" => "IA",
-  "Kansas" => "KS",
-  "Kentucky" => "KY",
-  "Louisiana" => "LA",
-  "Maine" => "ME",
-  "Maryland" => "MD",
-  "Massachusetts" => "MA",
-  "Michigan" => "MI",
-  "Minnesota" => "MN",
-  "Mississippi" => "MS",
-  "Missouri" => "MO",
-  "Montana" => "MT",
-  "Nebraska" => "NE",
-  "Nevada" => "NV",
-  "New Hampshire" => "NH",
-  "New Jersey" => "NJ",
-  "New Mexico" => "NM",
-  "New York" => "NY",
-  "North Carolina" => "NC",
-  "North Dakota" => "ND",
-  "Ohio" => "OH",
-  "Oklahoma" => "OK",
-  "Oregon" => "OR",
-  "Pennsylvania" => "PA",
-  "Rhode Island" => "RI",
-  "South Carolina" => "SC",
-  "South Dakota" => "SD",
-  "Tennessee" => "TN",
-  "Texas" => "TX",
-  "Utah" => "UT",
-  "Vermont" => "VT",
-  "Virginia" => "VA",
-  "Washington" => "WA",
-  "West Virginia" => "WV",
-  "Wisconsin" => "WI",
-  "Wyoming" => "WY",
-};
-
-pub static STATES: [&str; 50] = [
-    "AL", "AK", "AZ", "AR", "CA", "CO", "CT", "DE", "FL", "GA", "HI", "ID", "IL", "IN", "IA", "KS",
-    "KY", "LA", "ME", "MD", "MA", "MI", "MN", "MS", "MO", "MT", "NE", "NV", "NH", "NJ", "NM", "NY",
-    "NC", "ND", "OH", "OK", "OR", "PA", "RI", "SC", "SD", "TN", "TX", "UT", "VT", "VA", "WA", "WV",
-    "WI", "WY",
-];
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::de::{self, Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+
+pub use regions::{ParseRegionsError, Regions};
+pub use resolve::{normalize, Resolver};
+
+mod area;
+mod regions;
+mod resolve;
+
+macro_rules! states {
+    (
+        states { $($state:ident => ($state_code:literal, $state_name:literal, $state_fips:literal),)* }
+        territories { $($territory:ident => ($territory_code:literal, $territory_name:literal, $territory_fips:literal),)* }
+    ) => {
+        states! {
+            @impl
+            states [$($state),*]
+            territories [$($territory),*]
+            all {
+                $($state => ($state_code, $state_name, $state_fips),)*
+                $($territory => ($territory_code, $territory_name, $territory_fips),)*
+            }
+        }
+    };
+    (
+        @impl
+        states [$($state:ident),*]
+        territories [$($territory:ident),*]
+        all { $($variant:ident => ($code:literal, $name:literal, $fips:literal),)* }
+    ) => {
+        /// One of the 50 U.S. states, DC, or one of the inhabited territories. Serializes as (and
+        /// can be parsed from) its postal code.
+        #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+        pub enum State {
+            $($variant,)*
+        }
+
+        /// Every state, in alphabetical order by name.
+        pub static STATES: [State; 50] = [$(State::$state,)*];
+
+        /// DC and the inhabited territories, which are only included when selected with
+        /// [`Regions`].
+        pub static TERRITORIES: [State; 6] = [$(State::$territory,)*];
+
+        impl State {
+            /// The two letter USPS postal code, e.g. "MD".
+            pub fn code(self) -> &'static str {
+                match self {
+                    $(State::$variant => $code,)*
+                }
+            }
+
+            /// The full name of the state, e.g. "Maryland".
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(State::$variant => $name,)*
+                }
+            }
+
+            /// The two digit FIPS code, e.g. 24 for Maryland.
+            pub fn fips(self) -> u8 {
+                match self {
+                    $(State::$variant => $fips,)*
+                }
+            }
+
+            /// Look up a state by its postal code. Case sensitive, so "MD" works but "md" does not.
+            pub fn from_code(code: &str) -> Option<Self> {
+                match code {
+                    $($code => Some(State::$variant),)*
+                    _ => None,
+                }
+            }
+
+            /// Look up a state by its exact, title-case name.
+            pub fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    $($name => Some(State::$variant),)*
+                    _ => None,
+                }
+            }
+
+            /// Look up a state by its FIPS code.
+            pub fn from_fips(fips: u8) -> Option<Self> {
+                match fips {
+                    $($fips => Some(State::$variant),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+states! {
+    states {
+        Alabama => ("AL", "Alabama", 1),
+        Alaska => ("AK", "Alaska", 2),
+        Arizona => ("AZ", "Arizona", 4),
+        Arkansas => ("AR", "Arkansas", 5),
+        California => ("CA", "California", 6),
+        Colorado => ("CO", "Colorado", 8),
+        Connecticut => ("CT", "Connecticut", 9),
+        Delaware => ("DE", "Delaware", 10),
+        Florida => ("FL", "Florida", 12),
+        Georgia => ("GA", "Georgia", 13),
+        Hawaii => ("HI", "Hawaii", 15),
+        Idaho => ("ID", "Idaho", 16),
+        Illinois => ("IL", "Illinois", 17),
+        Indiana => ("IN", "Indiana", 18),
+        Iowa => ("IA", "Iowa", 19),
+        Kansas => ("KS", "Kansas", 20),
+        Kentucky => ("KY", "Kentucky", 21),
+        Louisiana => ("LA", "Louisiana", 22),
+        Maine => ("ME", "Maine", 23),
+        Maryland => ("MD", "Maryland", 24),
+        Massachusetts => ("MA", "Massachusetts", 25),
+        Michigan => ("MI", "Michigan", 26),
+        Minnesota => ("MN", "Minnesota", 27),
+        Mississippi => ("MS", "Mississippi", 28),
+        Missouri => ("MO", "Missouri", 29),
+        Montana => ("MT", "Montana", 30),
+        Nebraska => ("NE", "Nebraska", 31),
+        Nevada => ("NV", "Nevada", 32),
+        NewHampshire => ("NH", "New Hampshire", 33),
+        NewJersey => ("NJ", "New Jersey", 34),
+        NewMexico => ("NM", "New Mexico", 35),
+        NewYork => ("NY", "New York", 36),
+        NorthCarolina => ("NC", "North Carolina", 37),
+        NorthDakota => ("ND", "North Dakota", 38),
+        Ohio => ("OH", "Ohio", 39),
+        Oklahoma => ("OK", "Oklahoma", 40),
+        Oregon => ("OR", "Oregon", 41),
+        Pennsylvania => ("PA", "Pennsylvania", 42),
+        RhodeIsland => ("RI", "Rhode Island", 44),
+        SouthCarolina => ("SC", "South Carolina", 45),
+        SouthDakota => ("SD", "South Dakota", 46),
+        Tennessee => ("TN", "Tennessee", 47),
+        Texas => ("TX", "Texas", 48),
+        Utah => ("UT", "Utah", 49),
+        Vermont => ("VT", "Vermont", 50),
+        Virginia => ("VA", "Virginia", 51),
+        Washington => ("WA", "Washington", 53),
+        WestVirginia => ("WV", "West Virginia", 54),
+        Wisconsin => ("WI", "Wisconsin", 55),
+        Wyoming => ("WY", "Wyoming", 56),
+    }
+    territories {
+        DistrictOfColumbia => ("DC", "District of Columbia", 11),
+        PuertoRico => ("PR", "Puerto Rico", 72),
+        Guam => ("GU", "Guam", 66),
+        VirginIslands => ("VI", "U.S. Virgin Islands", 78),
+        AmericanSamoa => ("AS", "American Samoa", 60),
+        NorthernMarianaIslands => ("MP", "Northern Mariana Islands", 69),
+    }
+}
+
+impl State {
+    /// Iterate over the 50 states, in alphabetical order by name. Use [`Regions`] to include DC and
+    /// the territories.
+    pub fn iter() -> impl Iterator<Item = State> {
+        STATES.iter().copied()
+    }
+
+    /// Whether this is one of the 50 states (as opposed to DC or a territory).
+    pub fn is_state(self) -> bool {
+        !TERRITORIES.contains(&self)
+    }
+}
+
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// Returned when a string is neither a postal code nor the name of a state.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseStateError(String);
+
+impl fmt::Display for ParseStateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} is not a state", self.0)
+    }
+}
+
+impl Error for ParseStateError {}
+
+impl FromStr for State {
+    type Err = ParseStateError;
+
+    /// Accepts either a postal code ("MD") or an exact name ("Maryland").
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        State::from_code(s)
+            .or_else(|| State::from_name(s))
+            .ok_or_else(|| ParseStateError(s.to_owned()))
+    }
+}
+
+impl Serialize for State {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.code())
+    }
+}
+
+impl<'de> Deserialize<'de> for State {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
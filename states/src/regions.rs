@@ -0,0 +1,150 @@
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{State, STATES, TERRITORIES};
+
+/// Which places collectors should emit data for. Selected at runtime with the `REGIONS` env var,
+/// which can be "states" (the default), "dc" (states + DC), or "all" (states, DC, and territories).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Regions {
+    #[default]
+    States,
+    StatesAndDc,
+    All,
+}
+
+impl Regions {
+    /// Read the selection from the `REGIONS` env var, falling back to just the 50 states.
+    pub fn from_env() -> Result<Self, ParseRegionsError> {
+        match env::var("REGIONS") {
+            Ok(value) => value.parse(),
+            Err(_) => Ok(Regions::default()),
+        }
+    }
+
+    /// Every selected state / territory, states first.
+    pub fn iter(self) -> impl Iterator<Item = State> {
+        let territories: &'static [State] = match self {
+            Regions::States => &[],
+            Regions::StatesAndDc => &TERRITORIES[..1],
+            Regions::All => &TERRITORIES,
+        };
+        STATES.iter().chain(territories.iter()).copied()
+    }
+
+    pub fn contains(self, state: State) -> bool {
+        match self {
+            Regions::States => state.is_state(),
+            Regions::StatesAndDc => state.is_state() || state == State::DistrictOfColumbia,
+            Regions::All => true,
+        }
+    }
+}
+
+/// Returned when `REGIONS` is set to something other than "states", "dc", or "all".
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseRegionsError(String);
+
+impl fmt::Display for ParseRegionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} is not a valid region set, expected \"states\", \"dc\", or \"all\"",
+            self.0
+        )
+    }
+}
+
+impl Error for ParseRegionsError {}
+
+impl FromStr for Regions {
+    type Err = ParseRegionsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "states" => Ok(Regions::States),
+            "dc" => Ok(Regions::StatesAndDc),
+            "all" => Ok(Regions::All),
+            _ => Err(ParseRegionsError(s.to_owned())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_every_selection() {
+        assert_eq!("states".parse(), Ok(Regions::States));
+        assert_eq!("dc".parse(), Ok(Regions::StatesAndDc));
+        assert_eq!("all".parse(), Ok(Regions::All));
+        assert_eq!(" ALL ".parse(), Ok(Regions::All));
+    }
+
+    #[test]
+    fn rejects_anything_else() {
+        let error = "territories".parse::<Regions>().unwrap_err();
+        assert_eq!(error, ParseRegionsError("territories".to_owned()));
+        assert_eq!(
+            error.to_string(),
+            "\"territories\" is not a valid region set, expected \"states\", \"dc\", or \"all\""
+        );
+        assert!("".parse::<Regions>().is_err());
+    }
+
+    #[test]
+    fn reads_the_env_var() {
+        env::remove_var("REGIONS");
+        assert_eq!(Regions::from_env(), Ok(Regions::States));
+        env::set_var("REGIONS", "dc");
+        assert_eq!(Regions::from_env(), Ok(Regions::StatesAndDc));
+        env::set_var("REGIONS", "everywhere");
+        assert!(Regions::from_env().is_err());
+        env::remove_var("REGIONS");
+    }
+
+    #[test]
+    fn states() {
+        let regions = Regions::States;
+        assert_eq!(regions.iter().count(), 50);
+        assert!(regions.iter().all(State::is_state));
+        assert!(regions.contains(State::Maryland));
+        assert!(!regions.contains(State::DistrictOfColumbia));
+        assert!(!regions.contains(State::PuertoRico));
+    }
+
+    #[test]
+    fn dc() {
+        let regions = Regions::StatesAndDc;
+        assert_eq!(regions.iter().count(), 51);
+        assert_eq!(regions.iter().last(), Some(State::DistrictOfColumbia));
+        assert!(regions.contains(State::Maryland));
+        assert!(regions.contains(State::DistrictOfColumbia));
+        assert!(!regions.contains(State::PuertoRico));
+    }
+
+    #[test]
+    fn all() {
+        let regions = Regions::All;
+        let all: Vec<State> = regions.iter().collect();
+        assert_eq!(all.len(), 56);
+        assert_eq!(all[..50], STATES);
+        assert_eq!(all[50..], TERRITORIES);
+        assert!(all.iter().all(|state| regions.contains(*state)));
+    }
+
+    #[test]
+    fn iter_matches_contains() {
+        for regions in [Regions::States, Regions::StatesAndDc, Regions::All].iter() {
+            for state in Regions::All.iter() {
+                assert_eq!(
+                    regions.contains(state),
+                    regions.iter().any(|selected| selected == state)
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,277 @@
+use std::collections::BTreeSet;
+
+use crate::{Regions, State};
+
+/// Abbreviations (after normalization) which aren't postal codes, e.g. "Mass." or "Calif."
+static ALIASES: &[(&str, State)] = &[
+    ("ala", State::Alabama),
+    ("ariz", State::Arizona),
+    ("ark", State::Arkansas),
+    ("cal", State::California),
+    ("calif", State::California),
+    ("colo", State::Colorado),
+    ("conn", State::Connecticut),
+    ("del", State::Delaware),
+    ("districtofcolumbia", State::DistrictOfColumbia),
+    ("fla", State::Florida),
+    ("ill", State::Illinois),
+    ("ind", State::Indiana),
+    ("kan", State::Kansas),
+    ("kans", State::Kansas),
+    ("marianas", State::NorthernMarianaIslands),
+    ("mass", State::Massachusetts),
+    ("mich", State::Michigan),
+    ("minn", State::Minnesota),
+    ("miss", State::Mississippi),
+    ("mont", State::Montana),
+    ("ndak", State::NorthDakota),
+    ("neb", State::Nebraska),
+    ("nebr", State::Nebraska),
+    ("nev", State::Nevada),
+    ("nmex", State::NewMexico),
+    ("northernmarianas", State::NorthernMarianaIslands),
+    ("okla", State::Oklahoma),
+    ("ore", State::Oregon),
+    ("oreg", State::Oregon),
+    ("penn", State::Pennsylvania),
+    ("penna", State::Pennsylvania),
+    ("samoa", State::AmericanSamoa),
+    ("sdak", State::SouthDakota),
+    ("tenn", State::Tennessee),
+    ("tex", State::Texas),
+    ("unitedstatesvirginislands", State::VirginIslands),
+    ("virginislands", State::VirginIslands),
+    ("wash", State::Washington),
+    ("washingtondc", State::DistrictOfColumbia),
+    ("wis", State::Wisconsin),
+    ("wisc", State::Wisconsin),
+    ("wva", State::WestVirginia),
+    ("wyo", State::Wyoming),
+];
+
+/// Abbreviated first words which get expanded, so "N. Carolina" becomes "north carolina"
+static PREFIXES: &[(&str, &str)] = &[("n", "north"), ("s", "south"), ("w", "west")];
+
+/// Rows that show up in state tables but aren't states. These are dropped without a warning.
+static AGGREGATES: &[&str] = &[
+    "united states",
+    "us",
+    "usa",
+    "us total",
+    "total",
+    "national",
+];
+
+/// Lowercase, drop footnote markers and punctuation, split into words and strip words like
+/// "State" which only pad out the name.
+fn words(name: &str) -> Vec<String> {
+    let cleaned: String = name
+        .chars()
+        .filter(|c| !matches!(c, '.' | '\'' | '’' | 'ʻ' | '*' | '†' | '‡') && !c.is_ascii_digit())
+        .map(|c| {
+            if c.is_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                ' '
+            }
+        })
+        .collect();
+    let mut words: Vec<String> = cleaned.split_whitespace().map(str::to_owned).collect();
+    if words.len() > 2
+        && words[1] == "of"
+        && matches!(words[0].as_str(), "state" | "commonwealth" | "territory")
+    {
+        words.drain(..2);
+    }
+    if words.len() > 1 && words[words.len() - 1] == "state" {
+        words.pop();
+    }
+    words
+}
+
+/// Normalize a name so it can be compared to a lowercase state name. Drops punctuation, footnote
+/// markers and extra whitespace, and expands directional abbreviations, so "N. Carolina*"
+/// becomes "north carolina".
+pub fn normalize(name: &str) -> String {
+    let mut words = words(name);
+    if words.len() > 1 {
+        if let Some((_, expanded)) = PREFIXES.iter().find(|(short, _)| *short == words[0]) {
+            words[0] = (*expanded).to_owned();
+        }
+    }
+    words.join(" ")
+}
+
+impl State {
+    /// Resolve a state from a messy name as it appears in the wild. Accepts any case, extra
+    /// whitespace and punctuation, footnote markers, postal codes and common abbreviations, so
+    /// "NEW YORK", "New York State", "N. Carolina " and "Mass.*" all work. DC and the territories
+    /// are resolved too, so callers should filter with [`Regions::contains`].
+    pub fn resolve(name: &str) -> Option<Self> {
+        let normalized = normalize(name);
+        if let Some(state) = Regions::All
+            .iter()
+            .find(|state| normalize(state.name()) == normalized)
+        {
+            return Some(state);
+        }
+        // Abbreviations like "N.H." or "W. Va." are compared with all spaces removed
+        let squashed = words(name).concat();
+        Regions::All
+            .iter()
+            .find(|state| state.code().eq_ignore_ascii_case(&squashed))
+            .or_else(|| {
+                ALIASES
+                    .iter()
+                    .find(|(alias, _)| *alias == squashed)
+                    .map(|(_, state)| *state)
+            })
+    }
+}
+
+/// Resolves names with [`State::resolve`], remembering every name which could not be resolved
+/// so that scrapers can report them instead of silently dropping rows.
+#[derive(Debug, Default)]
+pub struct Resolver {
+    unresolved: BTreeSet<String>,
+}
+
+impl Resolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolve a single name, recording it if it isn't a state. Rows for the entire country
+    /// (e.g. "United States") are not recorded.
+    pub fn resolve(&mut self, name: &str) -> Option<State> {
+        let state = State::resolve(name);
+        if state.is_none() && !AGGREGATES.contains(&normalize(name).as_str()) {
+            self.unresolved.insert(name.trim().to_owned());
+        }
+        state
+    }
+
+    /// Every distinct name which failed to resolve so far.
+    pub fn unresolved(&self) -> impl Iterator<Item = &str> {
+        self.unresolved.iter().map(String::as_str)
+    }
+
+    /// Print a warning naming every input that failed to resolve, if there were any.
+    pub fn warn_unresolved(&self, source: &str) {
+        if !self.unresolved.is_empty() {
+            let names: Vec<&str> = self.unresolved().collect();
+            eprintln!(
+                "Warning: could not resolve states from {}: {:?}",
+                source, names
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_names() {
+        assert_eq!(State::resolve("Maryland"), Some(State::Maryland));
+        assert_eq!(State::resolve("New York"), Some(State::NewYork));
+        assert_eq!(
+            State::resolve("District of Columbia"),
+            Some(State::DistrictOfColumbia)
+        );
+        assert_eq!(State::resolve("Puerto Rico"), Some(State::PuertoRico));
+    }
+
+    #[test]
+    fn postal_codes() {
+        assert_eq!(State::resolve("MD"), Some(State::Maryland));
+        assert_eq!(State::resolve("md"), Some(State::Maryland));
+        assert_eq!(State::resolve("N.H."), Some(State::NewHampshire));
+        assert_eq!(State::resolve("D.C."), Some(State::DistrictOfColumbia));
+    }
+
+    #[test]
+    fn abbreviations() {
+        assert_eq!(State::resolve("Mass."), Some(State::Massachusetts));
+        assert_eq!(State::resolve("Calif."), Some(State::California));
+        assert_eq!(State::resolve("W. Va."), Some(State::WestVirginia));
+        assert_eq!(State::resolve("N. Carolina"), Some(State::NorthCarolina));
+        assert_eq!(State::resolve("S Dakota"), Some(State::SouthDakota));
+        assert_eq!(
+            State::resolve("Washington, D.C."),
+            Some(State::DistrictOfColumbia)
+        );
+        assert_eq!(
+            State::resolve("U.S. Virgin Islands"),
+            Some(State::VirginIslands)
+        );
+    }
+
+    #[test]
+    fn case_and_whitespace() {
+        assert_eq!(State::resolve("NEW YORK"), Some(State::NewYork));
+        assert_eq!(State::resolve("new york"), Some(State::NewYork));
+        assert_eq!(State::resolve("  New   York "), Some(State::NewYork));
+        assert_eq!(State::resolve("North\tDakota"), Some(State::NorthDakota));
+    }
+
+    #[test]
+    fn punctuation_and_footnotes() {
+        assert_eq!(State::resolve("Mass.*"), Some(State::Massachusetts));
+        assert_eq!(State::resolve("Texas†"), Some(State::Texas));
+        assert_eq!(State::resolve("Ohio (1)"), Some(State::Ohio));
+        assert_eq!(State::resolve("Hawaiʻi"), Some(State::Hawaii));
+        assert_eq!(State::resolve("Wisconsin3"), Some(State::Wisconsin));
+    }
+
+    #[test]
+    fn state_padding() {
+        assert_eq!(State::resolve("New York State"), Some(State::NewYork));
+        assert_eq!(State::resolve("Washington State"), Some(State::Washington));
+        assert_eq!(
+            State::resolve("Commonwealth of Pennsylvania"),
+            Some(State::Pennsylvania)
+        );
+        assert_eq!(
+            State::resolve("State of Rhode Island"),
+            Some(State::RhodeIsland)
+        );
+    }
+
+    #[test]
+    fn unresolvable_names() {
+        assert_eq!(State::resolve("Narnia"), None);
+        assert_eq!(State::resolve(""), None);
+        assert_eq!(State::resolve("ZZ"), None);
+    }
+
+    #[test]
+    fn resolver_records_failures() {
+        let mut resolver = Resolver::new();
+        assert_eq!(resolver.resolve("Mass."), Some(State::Massachusetts));
+        assert_eq!(resolver.resolve(" Narnia "), None);
+        assert_eq!(resolver.resolve("Narnia"), None);
+        assert_eq!(resolver.resolve("Atlantis"), None);
+        assert_eq!(
+            resolver.unresolved().collect::<Vec<_>>(),
+            ["Atlantis", "Narnia"]
+        );
+    }
+
+    #[test]
+    fn resolver_skips_national_rows() {
+        let mut resolver = Resolver::new();
+        assert_eq!(resolver.resolve("United States"), None);
+        assert_eq!(resolver.resolve("U.S. Total"), None);
+        assert_eq!(resolver.resolve("Total"), None);
+        assert_eq!(resolver.unresolved().count(), 0);
+    }
+
+    #[test]
+    fn normalize_expands_directions() {
+        assert_eq!(normalize("N. Carolina*"), "north carolina");
+        assert_eq!(normalize("W. Virginia"), "west virginia");
+        assert_eq!(normalize("N"), "n");
+    }
+}
@@ -0,0 +1,446 @@
+#![forbid(unsafe_code)]
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use dotenv::dotenv;
+use futures::stream::{self, StreamExt};
+use structopt::StructOpt;
+
+use collector::{AnyCollector, Http};
+use cost_of_living::CostOfLiving;
+use eia::Eia;
+use maryland::Maryland;
+use metrics::{
+    land_area, validate, validate_file, Derived, Metric, Normalize, Output, Per, Problem, Profile,
+};
+use noaa::Noaa;
+use nps::Nps;
+use solar_prices::SolarPrices;
+use states::{Regions, State};
+
+/// Collect data for whatstateshouldilivein.com. Output goes in generated and sources.json.
+#[derive(Debug, StructOpt)]
+struct Opt {
+    /// Names of the collectors to run, runs all of them if none are given
+    collectors: Vec<String>,
+    /// How many collectors to run at the same time
+    #[structopt(short, long, default_value = "4")]
+    jobs: usize,
+    /// List the available collectors and the env vars they need, then exit
+    #[structopt(long)]
+    list: bool,
+    /// Only validate every file already in generated against sources.json, then exit
+    #[structopt(long)]
+    check: bool,
+    /// Ignore every cached response and fetch it all again
+    #[structopt(long)]
+    refresh: bool,
+    /// Never touch the network, run entirely from cached responses no matter how old
+    #[structopt(long)]
+    offline: bool,
+}
+
+/// A collector, and why it can't run if it's configured wrong
+struct Configured {
+    collector: Box<dyn AnyCollector>,
+    /// Reported as the collector failing, so one bad env var doesn't stop the others or `--list`
+    error: Option<String>,
+}
+
+impl Configured {
+    fn new(collector: impl AnyCollector + 'static) -> Self {
+        Self {
+            collector: Box::new(collector),
+            error: None,
+        }
+    }
+}
+
+/// Every collector, in the order they are listed
+fn collectors(regions: Regions, http: &Http) -> Vec<Configured> {
+    let noaa = match Noaa::from_env(regions, http.clone()) {
+        Ok(noaa) => Configured::new(noaa),
+        // The defaults, so its files are still listed
+        Err(e) => Configured {
+            error: Some(format!("{:#}", e)),
+            ..Configured::new(Noaa::new(regions, http.clone()))
+        },
+    };
+    vec![
+        Configured::new(CostOfLiving::new(regions, http.clone())),
+        Configured::new(Eia::new(regions, http.clone())),
+        Configured::new(Maryland::new(regions, http.clone())),
+        noaa,
+        Configured::new(Nps::new(regions, http.clone())),
+        Configured::new(SolarPrices::new(regions)),
+    ]
+}
+
+/// A file written by a collector
+struct Produced {
+    file: String,
+    /// States with a value
+    count: usize,
+    /// Why states have no value, when the collector knows
+    missing: BTreeMap<State, String>,
+}
+
+enum Status {
+    Produced(Vec<Produced>),
+    Skipped(String),
+    Failed(String),
+}
+
+struct Report {
+    collector: &'static str,
+    /// Files the collector would produce, listed when it doesn't produce them (or only some of
+    /// them)
+    expected: Vec<String>,
+    status: Status,
+}
+
+async fn run_collector(configured: &Configured, output: &Output) -> Report {
+    let collector = configured.collector.as_ref();
+    let missing = collector.missing_env();
+    let status = if let Some(error) = &configured.error {
+        Status::Failed(error.clone())
+    } else if !missing.is_empty() {
+        Status::Skipped(format!("missing {}", missing.join(", ")))
+    } else {
+        match collector.collect().await {
+            Ok(metrics) => write(&metrics, output),
+            Err(e) => Status::Failed(format!("{:#}", e)),
+        }
+    };
+    Report {
+        collector: collector.name(),
+        expected: collector
+            .sources()
+            .into_iter()
+            .map(|source| source.source.into_owned())
+            .collect(),
+        status,
+    }
+}
+
+fn write(metrics: &[Metric], output: &Output) -> Status {
+    match output.write(metrics) {
+        Ok(()) => Status::Produced(
+            metrics
+                .iter()
+                .map(|metric| Produced {
+                    file: metric.source().source.to_string(),
+                    count: metric.values().values().filter(|v| v.is_some()).count(),
+                    missing: metric.missing().clone(),
+                })
+                .collect(),
+        ),
+        Err(e) => Status::Failed(format!("{:#}", e)),
+    }
+}
+
+/// Write every total in normalize.json divided by population or land area, if its inputs are in
+/// generated (from this run or an earlier one)
+fn normalize(output: &Output, regions: Regions) -> Result<Vec<Report>> {
+    let path = Path::new("normalize.json");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let reports = Normalize::read_all(path)?
+        .iter()
+        .map(|normalize| {
+            let denominator = match normalize.per {
+                Per::Capita => Some("population.json"),
+                Per::SquareMile => None,
+            };
+            let missing: Vec<&str> = std::iter::once(normalize.source.as_ref())
+                .chain(denominator)
+                .filter(|file| !output.dir().join(file).exists())
+                .collect();
+            let status = if !missing.is_empty() {
+                Status::Skipped(format!("no {} in generated", missing.join(", ")))
+            } else {
+                let normalized = output.read(&normalize.source).and_then(|metric| {
+                    let denominator = match denominator {
+                        Some(file) => output.read(file)?,
+                        None => land_area(regions),
+                    };
+                    normalize.apply(&metric, &denominator)
+                });
+                match normalized {
+                    Ok(metric) => write(&[metric], output),
+                    Err(e) => Status::Failed(format!("{:#}", e)),
+                }
+            };
+            Report {
+                collector: "normalize",
+                expected: vec![normalize.file()],
+                status,
+            }
+        })
+        .collect();
+    Ok(reports)
+}
+
+/// Write every metric in derived.json whose inputs are in generated. Entries are evaluated in
+/// order, so one can use those before it.
+fn derive(output: &Output, regions: Regions) -> Result<Vec<Report>> {
+    let path = Path::new("derived.json");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let reports = Derived::read_all(path)?
+        .iter()
+        .map(|derived| {
+            let status = match derived.expression() {
+                Ok(expression) => {
+                    let files = expression.files();
+                    let missing: Vec<&str> = files
+                        .iter()
+                        .map(String::as_str)
+                        .filter(|file| !output.dir().join(file).exists())
+                        .collect();
+                    if !missing.is_empty() {
+                        Status::Skipped(format!("no {} in generated", missing.join(", ")))
+                    } else {
+                        let derived = files
+                            .iter()
+                            .map(|file| Ok((file.clone(), output.read(file)?)))
+                            .collect::<Result<HashMap<_, _>>>()
+                            .and_then(|inputs| derived.evaluate(&inputs, regions));
+                        match derived {
+                            Ok(metric) => write(&[metric], output),
+                            Err(e) => Status::Failed(format!("{:#}", e)),
+                        }
+                    }
+                }
+                Err(e) => Status::Failed(format!("{:#}", e)),
+            };
+            Report {
+                collector: "derived",
+                expected: vec![derived.source.source.to_string()],
+                status,
+            }
+        })
+        .collect();
+    Ok(reports)
+}
+
+/// Score and rank states by every weight profile in `profiles`
+fn score(output: &Output, regions: Regions) -> Result<Vec<Report>> {
+    let dir = Path::new("profiles");
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let reports = Profile::read_all(dir)?
+        .iter()
+        .map(|profile| {
+            let missing: Vec<&str> = profile
+                .files()
+                .filter(|file| !output.dir().join(file).exists())
+                .collect();
+            let status = if !missing.is_empty() {
+                Status::Skipped(format!("no {} in generated", missing.join(", ")))
+            } else {
+                let scored = profile
+                    .files()
+                    .map(|file| Ok((file.to_owned(), output.read(file)?)))
+                    .collect::<Result<HashMap<_, _>>>()
+                    .and_then(|inputs| profile.score(&inputs, regions));
+                match scored {
+                    Ok(metrics) => write(&metrics, output),
+                    Err(e) => Status::Failed(format!("{:#}", e)),
+                }
+            };
+            Report {
+                collector: "score",
+                expected: vec![
+                    format!("score_{}.json", profile.id),
+                    format!("score_{}_rank.json", profile.id),
+                ],
+                status,
+            }
+        })
+        .collect();
+    Ok(reports)
+}
+
+fn print_summary(reports: &[Report]) {
+    let mut rows = vec![(
+        "COLLECTOR".to_owned(),
+        "FILE".to_owned(),
+        "STATUS".to_owned(),
+    )];
+    for report in reports {
+        let name = report.collector.to_owned();
+        match &report.status {
+            Status::Produced(files) => {
+                for produced in files {
+                    let status = if produced.missing.is_empty() {
+                        format!("produced ({} states)", produced.count)
+                    } else {
+                        format!(
+                            "produced ({} states, {} missing)",
+                            produced.count,
+                            produced.missing.len()
+                        )
+                    };
+                    rows.push((name.clone(), produced.file.clone(), status));
+                }
+                // A partial failure
+                for file in &report.expected {
+                    if !files.iter().any(|produced| produced.file == *file) {
+                        rows.push((name.clone(), file.clone(), "not produced".to_owned()));
+                    }
+                }
+            }
+            Status::Skipped(reason) => {
+                for file in &report.expected {
+                    rows.push((name.clone(), file.clone(), format!("skipped: {}", reason)));
+                }
+            }
+            Status::Failed(reason) => {
+                for file in &report.expected {
+                    rows.push((name.clone(), file.clone(), format!("FAILED: {}", reason)));
+                }
+            }
+        }
+    }
+    let name_width = rows.iter().map(|row| row.0.len()).max().unwrap_or(0);
+    let file_width = rows.iter().map(|row| row.1.len()).max().unwrap_or(0);
+    for (name, file, status) in rows {
+        println!(
+            "{:name_width$}  {:file_width$}  {}",
+            name,
+            file,
+            status,
+            name_width = name_width,
+            file_width = file_width
+        );
+    }
+    print_missing(reports);
+}
+
+/// Every state a produced file is missing, and why
+fn print_missing(reports: &[Report]) {
+    let missing: Vec<&Produced> = reports
+        .iter()
+        .filter_map(|report| match &report.status {
+            Status::Produced(files) => Some(files),
+            _ => None,
+        })
+        .flatten()
+        .filter(|produced| !produced.missing.is_empty())
+        .collect();
+    if missing.is_empty() {
+        return;
+    }
+    println!();
+    println!("MISSING STATES");
+    for produced in missing {
+        println!("{}", produced.file);
+        // States missing for the same reason share a line
+        let mut reasons: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (state, reason) in &produced.missing {
+            reasons.entry(reason).or_default().push(state.code());
+        }
+        for (reason, states) in reasons {
+            println!("  {}: {}", states.join(", "), reason);
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv().ok();
+    let opt = Opt::from_args();
+    let regions = Regions::from_env()?;
+    let all = collectors(regions, &http(&opt)?);
+    if opt.list {
+        for Configured { collector, .. } in &all {
+            println!(
+                "{} {}",
+                collector.name(),
+                collector.required_env().join(" ")
+            );
+        }
+        return Ok(());
+    }
+    let output = Output::default();
+    if opt.check {
+        return check(&validate(&output, regions)?);
+    }
+    let selected: Vec<&Configured> = if opt.collectors.is_empty() {
+        all.iter().collect()
+    } else {
+        opt.collectors
+            .iter()
+            .map(|name| {
+                all.iter()
+                    .find(|configured| configured.collector.name() == name)
+                    .ok_or_else(|| anyhow!("Unknown collector {}, try --list", name))
+            })
+            .collect::<Result<_>>()?
+    };
+
+    let mut reports: Vec<Report> = stream::iter(selected)
+        .map(|collector| run_collector(collector, &output))
+        .buffer_unordered(opt.jobs.max(1))
+        .collect()
+        .await;
+    reports.sort_by_key(|report| report.collector);
+    reports.extend(normalize(&output, regions)?);
+    reports.extend(derive(&output, regions)?);
+    reports.extend(score(&output, regions)?);
+    print_summary(&reports);
+
+    let failed = reports
+        .iter()
+        .filter(|report| matches!(report.status, Status::Failed(_)))
+        .count();
+    if failed > 0 {
+        return Err(anyhow!("{} collector(s) failed", failed));
+    }
+
+    let sources = output.read_sources()?;
+    let problems: Vec<Problem> = reports
+        .iter()
+        .filter_map(|report| match &report.status {
+            Status::Produced(files) => Some(files),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|produced| sources.iter().find(|source| source.source == produced.file))
+        .flat_map(|source| validate_file(&output, source, regions))
+        .collect();
+    check(&problems)
+}
+
+/// The client shared by every collector, with the cache overridden by `--refresh` / `--offline`
+fn http(opt: &Opt) -> Result<Http> {
+    let http = Http::from_env()?;
+    let cache = match http.cache() {
+        Some(cache) => cache.clone(),
+        None if opt.refresh || opt.offline => {
+            return Err(anyhow!(
+                "--refresh and --offline need HTTP_CACHE to not be off"
+            ))
+        }
+        None => return Ok(http),
+    };
+    let offline = opt.offline || cache.is_offline();
+    Ok(http.with_cache(Some(cache.refresh(opt.refresh).offline(offline))))
+}
+
+/// Print every problem and fail if there were any, so bad data never gets copied to the front end
+fn check(problems: &[Problem]) -> Result<()> {
+    for problem in problems {
+        eprintln!("Invalid: {}", problem);
+    }
+    if !problems.is_empty() {
+        return Err(anyhow!("{} problem(s) in generated data", problems.len()));
+    }
+    Ok(())
+}